@@ -0,0 +1,139 @@
+use crate::executor::MemoryManager;
+use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Fixed page size the cache tracks and transfers in, matching the device's
+/// natural block size.
+pub const CACHE_PAGE_SIZE: u64 = 4096;
+
+struct CachedPage {
+    data: Vec<u8>,
+    dirty: bool,
+}
+
+/// A userspace, byte-budgeted, write-back LRU cache sitting in front of a
+/// `MemoryManager`'s device memory, to model a DRAM tier over CXL/storage:
+/// reads and writes hit a DRAM-backed page slab when present, and only pay
+/// the device's latency on a miss or a dirty eviction.
+pub struct PageCache {
+    memory: Arc<MemoryManager>,
+    capacity_pages: usize,
+    pages: HashMap<u64, CachedPage>,
+    /// Least-recently-used order, oldest at the front.
+    lru: VecDeque<u64>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PageCache {
+    pub fn new(memory: Arc<MemoryManager>, budget_bytes: u64) -> Self {
+        let capacity_pages = (budget_bytes / CACHE_PAGE_SIZE).max(1) as usize;
+        Self {
+            memory,
+            capacity_pages,
+            pages: HashMap::new(),
+            lru: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    fn touch(&mut self, page_addr: u64) {
+        if let Some(pos) = self.lru.iter().position(|&p| p == page_addr) {
+            self.lru.remove(pos);
+        }
+        self.lru.push_back(page_addr);
+    }
+
+    /// Bring `page_addr` into the cache if it isn't already resident,
+    /// evicting the least-recently-used page (writing it back first if
+    /// dirty) to stay within `capacity_pages`.
+    fn ensure_page(&mut self, page_addr: u64) -> Result<()> {
+        if self.pages.contains_key(&page_addr) {
+            self.hits += 1;
+            self.touch(page_addr);
+            return Ok(());
+        }
+
+        self.misses += 1;
+        let data = self.memory.read_bytes(page_addr, CACHE_PAGE_SIZE as usize)?;
+
+        if self.pages.len() >= self.capacity_pages {
+            self.evict_one()?;
+        }
+        self.pages.insert(page_addr, CachedPage { data, dirty: false });
+        self.touch(page_addr);
+        Ok(())
+    }
+
+    fn evict_one(&mut self) -> Result<()> {
+        let Some(victim) = self.lru.pop_front() else { return Ok(()) };
+        if let Some(page) = self.pages.remove(&victim) {
+            if page.dirty {
+                self.memory.write_bytes(victim, &page.data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read `size` bytes starting at `address`, touching every `CACHE_PAGE_SIZE`
+    /// page the range overlaps.
+    pub fn read(&mut self, address: u64, size: usize) -> Result<Duration> {
+        let start = Instant::now();
+        let mut offset = address;
+        let end = address + size as u64;
+        while offset < end {
+            let page_addr = (offset / CACHE_PAGE_SIZE) * CACHE_PAGE_SIZE;
+            self.ensure_page(page_addr)?;
+            offset = page_addr + CACHE_PAGE_SIZE;
+        }
+        Ok(start.elapsed())
+    }
+
+    /// Write `size` bytes of the cache's fill pattern starting at `address`,
+    /// marking every touched page dirty for later write-back.
+    pub fn write(&mut self, address: u64, size: usize) -> Result<Duration> {
+        let start = Instant::now();
+        let mut offset = address;
+        let end = address + size as u64;
+        while offset < end {
+            let page_addr = (offset / CACHE_PAGE_SIZE) * CACHE_PAGE_SIZE;
+            self.ensure_page(page_addr)?;
+            let page = self.pages.get_mut(&page_addr).expect("just ensured");
+            let lo = (offset - page_addr) as usize;
+            let hi = (end.min(page_addr + CACHE_PAGE_SIZE) - page_addr) as usize;
+            page.data[lo..hi].iter_mut().for_each(|b| *b = 0xAA);
+            page.dirty = true;
+            offset = page_addr + CACHE_PAGE_SIZE;
+        }
+        Ok(start.elapsed())
+    }
+
+    /// Write back every dirty page still resident. Called once execution
+    /// finishes so no write is lost to a page that was never evicted.
+    pub fn finalize(&mut self) -> Result<()> {
+        let dirty: Vec<u64> = self
+            .pages
+            .iter()
+            .filter(|(_, page)| page.dirty)
+            .map(|(&addr, _)| addr)
+            .collect();
+
+        for addr in dirty {
+            let page = self.pages.get_mut(&addr).expect("collected from self.pages");
+            self.memory.write_bytes(addr, &page.data)?;
+            page.dirty = false;
+        }
+        Ok(())
+    }
+}
@@ -5,14 +5,26 @@ use serde::{Deserialize, Serialize};
 #[serde(tag = "op")]
 pub enum Operation {
     #[serde(rename = "read")]
-    Read { addr: u64, size: u64, thread: u32 },
-    
+    Read {
+        #[serde(deserialize_with = "deserialize_size_value")]
+        addr: u64,
+        #[serde(deserialize_with = "deserialize_size_value")]
+        size: u64,
+        thread: u32,
+    },
+
     #[serde(rename = "write")]
-    Write { addr: u64, size: u64, thread: u32 },
-    
+    Write {
+        #[serde(deserialize_with = "deserialize_size_value")]
+        addr: u64,
+        #[serde(deserialize_with = "deserialize_size_value")]
+        size: u64,
+        thread: u32,
+    },
+
     #[serde(rename = "cpu")]
     Cpu { cycles: u64, thread: u32 },
-    
+
     #[serde(rename = "gpu")]
     Gpu { kernel: String, thread: u32 },
 }
@@ -33,7 +45,9 @@ pub struct AddressMap {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryRegion {
     pub name: String,
+    #[serde(deserialize_with = "deserialize_size_value")]
     pub base: u64,
+    #[serde(deserialize_with = "deserialize_size_value")]
     pub size: u64,
     #[serde(rename = "type")]
     pub region_type: RegionType,
@@ -68,6 +82,9 @@ pub struct ThreadMapping {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionConfig {
     pub duration_seconds: Option<u64>,
+    /// Bytes/sec cap. Accepts a raw number, a bandwidth string like
+    /// `"100MB/s"`, or `"unlimited"` (same as omitting the field).
+    #[serde(default, deserialize_with = "deserialize_rate_limit")]
     pub rate_limit: Option<u64>,
     pub warmup_seconds: Option<u64>,
     pub metrics_interval: Option<u64>,
@@ -79,6 +96,15 @@ pub struct WorkloadSpec {
     pub name: String,
     pub workload_type: WorkloadType,
     pub params: std::collections::HashMap<String, serde_json::Value>,
+    /// RNG seed driving every random decision made while generating this workload.
+    /// `None` falls back to a fixed default, so a spec without a seed still
+    /// generates the same pattern on every run.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Force generation across threads to run in parallel (`Some(true)`) or stay
+    /// serial (`Some(false)`). `None` decides based on `operations` size.
+    #[serde(default)]
+    pub parallel: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,12 +117,26 @@ pub enum WorkloadType {
     Analytics,
     Cache,
     Mixed,
+    Zipfian,
+    /// Operation-mix workload with a prefill phase, modeled on universal
+    /// KV-store benchmarks (read/insert/update/remove over a churning key set).
+    Mix,
+    /// Tiered DRAM/CXL workload: a small hot set is kept resident in a DRAM
+    /// address range while a cold remainder lives in a CXL address range,
+    /// with cold blocks migrated to DRAM via explicit Read+Write pairs once
+    /// they cross a configured access threshold.
+    Tiered,
 }
 
 /// Runtime statistics
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ThreadStats {
     pub thread_id: u32,
+    /// Which cluster node produced this thread's stats, set when results are
+    /// folded together by `distributed::aggregate_results`. `None` for a
+    /// single-node `Exec` run.
+    #[serde(default)]
+    pub node_id: Option<u32>,
     pub operations_completed: u64,
     pub bytes_read: u64,
     pub bytes_written: u64,
@@ -104,10 +144,37 @@ pub struct ThreadStats {
     pub total_latency_ns: u64,
     pub min_latency_ns: u64,
     pub max_latency_ns: u64,
+    /// Retries spent on `CompareExchange` ops before they succeeded, whether
+    /// from real CAS failure or an injected `cas_fail_rate` retry.
+    pub atomic_retries: u64,
+    /// This thread's own tail latency, unmerged with the other threads'
+    /// histograms (see `ExecutionResults.read_latency_percentiles` for the
+    /// pattern-wide view).
+    pub read_latency_percentiles: LatencyPercentiles,
+    pub write_latency_percentiles: LatencyPercentiles,
+    pub cpu_latency_percentiles: LatencyPercentiles,
+}
+
+/// Tail-latency percentiles reconstructed from a log-linear (HDR-style)
+/// histogram; see `executor::LatencyHistogram`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50_ns: u64,
+    pub p90_ns: u64,
+    pub p99_ns: u64,
+    pub p999_ns: u64,
+}
+
+/// Hit/miss counters for the optional `cache::PageCache` tier in front of
+/// device memory.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
 }
 
 /// Execution results
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ExecutionResults {
     pub pattern_name: String,
     pub total_duration_ns: u64,
@@ -119,7 +186,300 @@ pub struct ExecutionResults {
     pub read_throughput_mbps: f64,
     pub write_throughput_mbps: f64,
     pub operations_per_second: f64,
+    pub read_latency_percentiles: LatencyPercentiles,
+    pub write_latency_percentiles: LatencyPercentiles,
+    pub cpu_latency_percentiles: LatencyPercentiles,
     pub thread_stats: Vec<ThreadStats>,
+    /// Populated when `PatternSpec.cache_budget_bytes` is set on a run with a
+    /// `device_path`; system-memory runs have no cache.
+    pub cache_stats: Option<CacheStats>,
+}
+
+/// Which type of operation a `ThreadOperation` performs. Kept separate from
+/// the generator-facing `Operation` enum: this is the denser, iteration- and
+/// stride-aware schedule `PatternExecutor` actually walks per thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpType {
+    Read,
+    Write,
+    Cpu,
+    /// Atomic `fetch_add` on an 8-byte-aligned address, for coherence/
+    /// contention testing on a shared region.
+    #[serde(rename = "fetch_add")]
+    FetchAdd,
+    /// Atomic `compare_exchange_weak` on an 8-byte-aligned address.
+    #[serde(rename = "cas")]
+    CompareExchange,
+}
+
+/// How a `ThreadOperation` picks its address each iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressMode {
+    /// Use `ThreadOperation.address`, walking it by `stride` each iteration.
+    #[default]
+    Fixed,
+    /// Draw a random offset within the working set each iteration, reusing a
+    /// recently-touched offset from a per-thread pool with probability
+    /// `ThreadPattern.reuse_rate` to model allocator-style temporal locality.
+    ReusePool,
+}
+
+/// One fragment of a vectored (scatter/gather) device I/O request: a
+/// `length`-byte buffer segment, transferred via the next sequential
+/// `O_DIRECT`-aligned position in a single `preadv`/`pwritev` call starting at
+/// the operation's address.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct IoFragment {
+    pub length: usize,
+}
+
+/// One operation within a `ThreadPattern`. Unlike the flat generator
+/// `Operation`, a `ThreadOperation` can repeat itself (`iterations`), walk a
+/// `stride` between repeats, and insert `think_time_ns` pauses, which is what
+/// `PatternExecutor` needs to replay a compiled-down schedule efficiently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadOperation {
+    pub op_type: OpType,
+    pub address: Option<u64>,
+    pub size: Option<usize>,
+    pub cpu_cycles: Option<u64>,
+    pub stride: Option<u64>,
+    pub iterations: Option<u64>,
+    pub think_time_ns: Option<u64>,
+    /// When set, this op gathers/scatters across these fragments in one
+    /// `preadv`/`pwritev` call instead of a single `size`-byte transfer.
+    #[serde(default)]
+    pub fragments: Option<Vec<IoFragment>>,
+    /// How to pick `address` each iteration; see `AddressMode`.
+    #[serde(default)]
+    pub address_mode: AddressMode,
+    /// `FetchAdd`'s increment, or `CompareExchange`'s new value.
+    pub atomic_value: Option<u64>,
+    /// `CompareExchange`'s expected current value.
+    pub atomic_expected: Option<u64>,
+}
+
+/// The operations a single worker thread runs, optionally confined to a
+/// working-set window and repeated as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadPattern {
+    pub thread_id: usize,
+    pub working_set_base: Option<u64>,
+    pub working_set_size: Option<u64>,
+    pub repeat_pattern: Option<u64>,
+    pub operations: Vec<ThreadOperation>,
+    /// RNG seed for this thread's `AddressMode::ReusePool` draws (and any
+    /// other per-thread randomness); `None` falls back to a fixed default.
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
+    /// Probability `[0, 1]` that a `ReusePool` address is drawn from the
+    /// reuse pool instead of freshly at random. `None` behaves as `0.0`.
+    #[serde(default)]
+    pub reuse_rate: Option<f64>,
+    /// Given a reuse, probability `[0, 1]` that the offset is drawn from
+    /// another thread's pool instead of this thread's own, to deliberately
+    /// induce cross-tier/cross-core traffic. `None` behaves as `0.0`.
+    #[serde(default)]
+    pub cross_thread_reuse_rate: Option<f64>,
+    /// Probability `[0, 1]` that a hardware-successful `CompareExchange` is
+    /// treated as failed and retried anyway, to model contention-induced
+    /// retry storms. `None` behaves as `0.0`.
+    #[serde(default)]
+    pub cas_fail_rate: Option<f64>,
+}
+
+/// Which `IoEngine` implementation `PatternExecutor` should drive the device
+/// path with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoEngineKind {
+    /// Blocking `pread`/`pwrite` per request.
+    Sync,
+    /// Asynchronous io_uring, keeping `queue_depth` requests outstanding.
+    IoUring,
+}
+
+/// The schedule `PatternExecutor` runs: per-thread operations against either
+/// system memory or a device, with the I/O engine and pipeline depth to use
+/// when the device path is asynchronous.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternSpec {
+    pub name: String,
+    pub memory_size: u64,
+    pub num_threads: usize,
+    pub thread_patterns: Vec<ThreadPattern>,
+    pub device_path: Option<String>,
+    pub use_mmap: bool,
+    #[serde(default = "default_io_engine")]
+    pub io_engine: IoEngineKind,
+    #[serde(default = "default_queue_depth")]
+    pub queue_depth: usize,
+    /// Size of the optional DRAM write-back page cache fronting device
+    /// memory. `None` disables the cache and goes straight to the device.
+    #[serde(default)]
+    pub cache_budget_bytes: Option<u64>,
+    /// Emit a JSON-lines `MetricsSnapshot` every this many milliseconds while
+    /// executing, to `metrics_output_path` or stdout. `None` disables it.
+    #[serde(default)]
+    pub metrics_interval_ms: Option<u64>,
+    /// Destination file for periodic snapshots when `metrics_interval_ms` is
+    /// set. `None` writes to stdout so the series can be tailed live.
+    #[serde(default)]
+    pub metrics_output_path: Option<String>,
+    /// Unix-domain socket accepting `pause`/`resume`/`set-rate <bw>`/`stats`
+    /// commands while the pattern runs. `None` disables runtime control.
+    #[serde(default)]
+    pub control_sock_path: Option<String>,
+}
+
+impl PatternSpec {
+    /// Compile a flat `Pattern` (the generator's output, and what `exec`/
+    /// `cluster` load from disk) down into the dense per-thread schedule
+    /// `PatternExecutor` actually runs. `address_map`'s regions only widen
+    /// `memory_size` so addresses that land in a configured region (e.g. a
+    /// CXL tier above the pattern's own observed addresses) stay in bounds;
+    /// `schedule_map`'s CPU/GPU affinities aren't consumed here since
+    /// `PatternExecutor` doesn't pin OS threads.
+    ///
+    /// `execution_config.metrics_interval` becomes `metrics_interval_ms`
+    /// directly (both are already milliseconds); `metrics_output_path` isn't
+    /// set here since `ExecutionConfig` has no file-path field of its own —
+    /// callers that want snapshots redirected to a file set it on the
+    /// returned `PatternSpec` afterwards.
+    ///
+    /// `execution_config.rate_limit`/`duration_seconds`/`warmup_seconds`
+    /// have no `PatternSpec` equivalent yet: `PatternExecutor` runs a
+    /// thread's operations to completion rather than for a wall-clock
+    /// duration. That's a pre-existing gap this conversion doesn't paper
+    /// over.
+    pub fn from_pattern(
+        pattern: Pattern,
+        address_map: Option<&AddressMap>,
+        _schedule_map: Option<&ScheduleMap>,
+        execution_config: &ExecutionConfig,
+    ) -> anyhow::Result<Self> {
+        let mut num_threads = 0u32;
+        let mut memory_size = 0u64;
+        let mut by_thread: std::collections::BTreeMap<u32, Vec<ThreadOperation>> = std::collections::BTreeMap::new();
+
+        for op in pattern.operations {
+            let (thread, thread_op, addr_end) = match op {
+                Operation::Read { addr, size, thread } => (thread, read_write_thread_op(OpType::Read, addr, size), addr + size),
+                Operation::Write { addr, size, thread } => (thread, read_write_thread_op(OpType::Write, addr, size), addr + size),
+                Operation::Cpu { cycles, thread } => (thread, cpu_thread_op(cycles), 0),
+                Operation::Gpu { thread, .. } => {
+                    anyhow::bail!(
+                        "thread {thread}: Gpu operations have no PatternExecutor equivalent (OpType has no Gpu variant)"
+                    );
+                }
+            };
+
+            num_threads = num_threads.max(thread + 1);
+            memory_size = memory_size.max(addr_end);
+            by_thread.entry(thread).or_default().push(thread_op);
+        }
+
+        // Widen memory_size to cover every mapped region too, so an address
+        // resolved against a configured AddressMap region stays in bounds
+        // even if the pattern itself never happened to touch its tail end.
+        if let Some(address_map) = address_map {
+            for region in &address_map.memory_regions {
+                memory_size = memory_size.max(region.base + region.size);
+            }
+        }
+
+        let num_threads = num_threads.max(1) as usize;
+        let thread_patterns = (0..num_threads)
+            .map(|thread_id| ThreadPattern {
+                thread_id,
+                working_set_base: None,
+                working_set_size: None,
+                repeat_pattern: None,
+                operations: by_thread.remove(&(thread_id as u32)).unwrap_or_default(),
+                rng_seed: None,
+                reuse_rate: None,
+                cross_thread_reuse_rate: None,
+                cas_fail_rate: None,
+            })
+            .collect();
+
+        Ok(PatternSpec {
+            name: pattern.name,
+            memory_size: memory_size.max(1),
+            num_threads,
+            thread_patterns,
+            device_path: None,
+            use_mmap: false,
+            io_engine: default_io_engine(),
+            queue_depth: default_queue_depth(),
+            cache_budget_bytes: None,
+            metrics_interval_ms: execution_config.metrics_interval,
+            metrics_output_path: None,
+            control_sock_path: None,
+        })
+    }
+}
+
+/// One `ThreadOperation` iteration wrapping a single flat `Operation::Read`/
+/// `Operation::Write`, fixed-addressed since `Pattern` has no stride/repeat.
+fn read_write_thread_op(op_type: OpType, addr: u64, size: u64) -> ThreadOperation {
+    ThreadOperation {
+        op_type,
+        address: Some(addr),
+        size: Some(size as usize),
+        cpu_cycles: None,
+        stride: None,
+        iterations: Some(1),
+        think_time_ns: None,
+        fragments: None,
+        address_mode: AddressMode::Fixed,
+        atomic_value: None,
+        atomic_expected: None,
+    }
+}
+
+/// One `ThreadOperation` iteration wrapping a single flat `Operation::Cpu`.
+fn cpu_thread_op(cycles: u64) -> ThreadOperation {
+    ThreadOperation {
+        op_type: OpType::Cpu,
+        address: None,
+        size: None,
+        cpu_cycles: Some(cycles),
+        stride: None,
+        iterations: Some(1),
+        think_time_ns: None,
+        fragments: None,
+        address_mode: AddressMode::Fixed,
+        atomic_value: None,
+        atomic_expected: None,
+    }
+}
+
+/// One periodic sample emitted while `metrics_interval_ms` is set: enough to
+/// plot cumulative progress and instantaneous throughput over the life of a
+/// run without stopping the worker threads to collect it.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    pub elapsed_seconds: f64,
+    pub cumulative_operations: u64,
+    pub cumulative_bytes_read: u64,
+    pub cumulative_bytes_written: u64,
+    pub average_latency_ns: f64,
+    /// Throughput since the previous snapshot, not since the run started.
+    pub instantaneous_read_mbps: f64,
+    pub instantaneous_write_mbps: f64,
+    pub cumulative_read_mbps: f64,
+    pub cumulative_write_mbps: f64,
+}
+
+fn default_io_engine() -> IoEngineKind {
+    IoEngineKind::Sync
+}
+
+fn default_queue_depth() -> usize {
+    32
 }
 
 /// Utility functions
@@ -137,6 +497,15 @@ pub fn parse_size_string(size_str: &str) -> anyhow::Result<u64> {
         Ok((num * 1024.0) as u64)
     } else if let Some(num_str) = size_str.strip_suffix("B") {
         Ok(num_str.parse()?)
+    } else if let Some(num_str) = size_str.strip_suffix('G') {
+        let num: f64 = num_str.parse()?;
+        Ok((num * 1024.0 * 1024.0 * 1024.0) as u64)
+    } else if let Some(num_str) = size_str.strip_suffix('M') {
+        let num: f64 = num_str.parse()?;
+        Ok((num * 1024.0 * 1024.0) as u64)
+    } else if let Some(num_str) = size_str.strip_suffix('K') {
+        let num: f64 = num_str.parse()?;
+        Ok((num * 1024.0) as u64)
     } else {
         Ok(size_str.parse()?)
     }
@@ -153,6 +522,115 @@ pub fn parse_bandwidth_string(bw_str: &str) -> anyhow::Result<Option<u64>> {
     Ok(Some(bytes_per_sec))
 }
 
+/// Resolve a `--workload-type`/DSL `type=` string to a `WorkloadType`.
+pub fn parse_workload_type(type_str: &str) -> anyhow::Result<WorkloadType> {
+    Ok(match type_str {
+        "sequential" => WorkloadType::Sequential,
+        "random" => WorkloadType::Random,
+        "hotspot" => WorkloadType::Hotspot,
+        "database" => WorkloadType::Database,
+        "analytics" => WorkloadType::Analytics,
+        "cache" => WorkloadType::Cache,
+        "mixed" => WorkloadType::Mixed,
+        "zipfian" => WorkloadType::Zipfian,
+        "mix" => WorkloadType::Mix,
+        "tiered" => WorkloadType::Tiered,
+        other => anyhow::bail!("Unknown workload type: {}", other),
+    })
+}
+
+/// Accepts either a raw number or a size string like `"4KB"`, for fields
+/// that used to require pre-computed byte counts.
+fn deserialize_size_value<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SizeValue {
+        Number(u64),
+        Text(String),
+    }
+
+    match SizeValue::deserialize(deserializer)? {
+        SizeValue::Number(n) => Ok(n),
+        SizeValue::Text(s) => parse_size_string(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Accepts a raw number, a bandwidth string like `"100MB/s"`, or `"unlimited"`.
+fn deserialize_rate_limit<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RateLimitValue {
+        Number(u64),
+        Text(String),
+    }
+
+    match Option::<RateLimitValue>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(RateLimitValue::Number(n)) => Ok(Some(n)),
+        Some(RateLimitValue::Text(s)) => parse_bandwidth_string(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Inline `key=value,key=value` DSL for quick runs without hand-writing a
+/// workload JSON file, e.g. `"type=random,ops=1000,threads=8,read_ratio=0.7,bs=4K"`.
+impl std::str::FromStr for WorkloadSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> anyhow::Result<Self> {
+        let mut workload_type_str = None;
+        let mut params = std::collections::HashMap::new();
+
+        for pair in spec.split(',') {
+            let pair = pair.trim();
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("expected key=value in workload spec, got '{}'", pair))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "type" => workload_type_str = Some(value.to_string()),
+                "ops" | "operations" => {
+                    params.insert("operations".to_string(), serde_json::Value::Number(value.parse::<u64>()?.into()));
+                }
+                "threads" => {
+                    params.insert("threads".to_string(), serde_json::Value::Number(value.parse::<u32>()?.into()));
+                }
+                "read_ratio" => {
+                    let ratio: f64 = value.parse()?;
+                    let number = serde_json::Number::from_f64(ratio)
+                        .ok_or_else(|| anyhow::anyhow!("invalid read_ratio: {}", value))?;
+                    params.insert("read_ratio".to_string(), serde_json::Value::Number(number));
+                }
+                "bs" | "block_size" => {
+                    let bytes = parse_size_string(value)?;
+                    params.insert("block_size".to_string(), serde_json::Value::Number(bytes.into()));
+                }
+                other => anyhow::bail!("Unknown workload spec key: {}", other),
+            }
+        }
+
+        let workload_type_str = workload_type_str.ok_or_else(|| anyhow::anyhow!("workload spec must set 'type'"))?;
+        let workload_type = parse_workload_type(&workload_type_str)?;
+
+        Ok(WorkloadSpec {
+            name: format!("{}_generated", workload_type_str),
+            workload_type,
+            params,
+            seed: None,
+            parallel: None,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,230 @@
+//! TCP-based distributed pattern execution: a coordinator ships each node its
+//! slice of a `Pattern`, starts every node together behind a start barrier so
+//! their executions begin at about the same instant (`warmup_seconds` is not
+//! applied), then folds the per-node `ExecutionResults` into
+//! one aggregate. Exists because a single process can't saturate a CXL
+//! fabric pooling memory across several hosts.
+
+use crate::common::{AddressMap, ExecutionConfig, ExecutionResults, Pattern, PatternSpec, ScheduleMap};
+use crate::executor::PatternExecutor;
+use anyhow::{Context, Result};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// One worker node: where to reach it, and which operation threads it runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeConfig {
+    pub node_id: u32,
+    pub addr: String,
+    pub thread_start: u32,
+    pub thread_end: u32,
+}
+
+/// The node list shipped alongside the pattern, read from its own JSON file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    pub nodes: Vec<NodeConfig>,
+}
+
+/// Everything a worker needs to run its slice of a distributed execution,
+/// sent as a single framed JSON message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Job {
+    pattern: Pattern,
+    address_map: Option<AddressMap>,
+    schedule_map: Option<ScheduleMap>,
+    execution_config: ExecutionConfig,
+}
+
+/// Byte a worker waits for after sending its readiness, so every node's
+/// `execute()` call starts at (close to) the same instant.
+const START_SIGNAL: u8 = 0x01;
+
+fn write_message<T: Serialize>(stream: &mut TcpStream, value: &T) -> Result<()> {
+    let bytes = serde_json::to_vec(value)?;
+    stream.write_u32::<LittleEndian>(bytes.len() as u32)?;
+    stream.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_message<T: DeserializeOwned>(stream: &mut TcpStream) -> Result<T> {
+    let len = stream.read_u32::<LittleEndian>()? as usize;
+    let mut bytes = vec![0u8; len];
+    stream.read_exact(&mut bytes)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Restrict `pattern` to the operations belonging to `[thread_start, thread_end]`,
+/// rebasing each op's thread to start at 0. Without rebasing, `PatternSpec::
+/// from_pattern` would size the node's thread count off the original absolute
+/// IDs, spawning (and reporting on) a batch of phantom empty threads below
+/// `thread_start` for every node in a typical contiguous-range cluster config.
+fn slice_pattern_for_node(pattern: &Pattern, thread_start: u32, thread_end: u32) -> Pattern {
+    let operations = pattern
+        .operations
+        .iter()
+        .filter(|op| {
+            let thread = match op {
+                crate::common::Operation::Read { thread, .. }
+                | crate::common::Operation::Write { thread, .. }
+                | crate::common::Operation::Cpu { thread, .. }
+                | crate::common::Operation::Gpu { thread, .. } => *thread,
+            };
+            (thread_start..=thread_end).contains(&thread)
+        })
+        .cloned()
+        .map(|op| rebase_operation_thread(op, thread_start))
+        .collect();
+
+    Pattern { name: pattern.name.clone(), operations }
+}
+
+/// Shift an operation's `thread` down by `thread_start`, after filtering has
+/// already established it falls in range.
+fn rebase_operation_thread(op: crate::common::Operation, thread_start: u32) -> crate::common::Operation {
+    use crate::common::Operation;
+    match op {
+        Operation::Read { addr, size, thread } => Operation::Read { addr, size, thread: thread - thread_start },
+        Operation::Write { addr, size, thread } => Operation::Write { addr, size, thread: thread - thread_start },
+        Operation::Cpu { cycles, thread } => Operation::Cpu { cycles, thread: thread - thread_start },
+        Operation::Gpu { kernel, thread } => Operation::Gpu { kernel, thread: thread - thread_start },
+    }
+}
+
+/// Worker side: accept jobs on `addr` and run each one locally, the same way
+/// `Commands::Exec` runs a standalone pattern.
+pub fn serve(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).with_context(|| format!("failed to bind {addr}"))?;
+    println!("Listening for cluster jobs on {addr}");
+
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        let peer = stream.peer_addr()?;
+        println!("Accepted job from {peer}");
+
+        match handle_job(&mut stream) {
+            Ok(()) => println!("Job from {peer} completed"),
+            Err(e) => eprintln!("job from {peer} failed: {e:#}"),
+        }
+    }
+    Ok(())
+}
+
+fn handle_job(stream: &mut TcpStream) -> Result<()> {
+    let job: Job = read_message(stream)?;
+    let spec = PatternSpec::from_pattern(
+        job.pattern,
+        job.address_map.as_ref(),
+        job.schedule_map.as_ref(),
+        &job.execution_config,
+    )?;
+    let executor = PatternExecutor::new(spec)?;
+
+    // Signal readiness, then wait for the coordinator's start barrier so
+    // every node begins executing at (close to) the same instant.
+    stream.write_u8(START_SIGNAL)?;
+    let mut go = [0u8; 1];
+    stream.read_exact(&mut go)?;
+
+    let results = executor.execute()?;
+    write_message(stream, &results)?;
+    Ok(())
+}
+
+/// Coordinator side: ship every node its slice of `pattern`, start them
+/// together, and fold the results into one `ExecutionResults`.
+pub fn run_cluster(
+    pattern: Pattern,
+    address_map: Option<AddressMap>,
+    schedule_map: Option<ScheduleMap>,
+    execution_config: ExecutionConfig,
+    cluster: ClusterConfig,
+) -> Result<ExecutionResults> {
+    // Connect to every node and ship its job up front; each worker then
+    // blocks on the start barrier below instead of starting immediately.
+    let mut connections = Vec::with_capacity(cluster.nodes.len());
+    for node in &cluster.nodes {
+        let mut stream = TcpStream::connect(&node.addr)
+            .with_context(|| format!("failed to connect to node {} at {}", node.node_id, node.addr))?;
+
+        let job = Job {
+            pattern: slice_pattern_for_node(&pattern, node.thread_start, node.thread_end),
+            address_map: address_map.clone(),
+            schedule_map: schedule_map.clone(),
+            execution_config: execution_config.clone(),
+        };
+        write_message(&mut stream, &job)?;
+
+        let mut ready = [0u8; 1];
+        stream.read_exact(&mut ready)?;
+
+        connections.push((node.node_id, stream));
+    }
+
+    let start_time = Instant::now();
+    for (_, stream) in &mut connections {
+        stream.write_u8(START_SIGNAL)?;
+    }
+
+    // Collect each node's results concurrently so one slow node doesn't
+    // serialize behind another's full execution time.
+    let handles: Vec<_> = connections
+        .into_iter()
+        .map(|(node_id, mut stream)| {
+            thread::spawn(move || -> Result<(u32, ExecutionResults)> {
+                let results: ExecutionResults = read_message(&mut stream)?;
+                Ok((node_id, results))
+            })
+        })
+        .collect();
+
+    let mut per_node = Vec::with_capacity(handles.len());
+    for handle in handles {
+        per_node.push(handle.join().expect("cluster result thread panicked")?);
+    }
+
+    let total_wall_clock = start_time.elapsed();
+    Ok(aggregate_results(total_wall_clock, per_node))
+}
+
+/// Fold every node's `ExecutionResults` into one, summing totals and
+/// re-deriving throughput/ops-per-second over the coordinator's measured
+/// wall-clock rather than any single node's.
+fn aggregate_results(total_wall_clock: Duration, per_node: Vec<(u32, ExecutionResults)>) -> ExecutionResults {
+    let mut aggregate = ExecutionResults::default();
+    aggregate.pattern_name = per_node
+        .first()
+        .map(|(_, r)| r.pattern_name.clone())
+        .unwrap_or_default();
+    aggregate.total_duration_ns = total_wall_clock.as_nanos() as u64;
+
+    for (node_id, mut result) in per_node {
+        aggregate.total_operations += result.total_operations;
+        aggregate.total_bytes_read += result.total_bytes_read;
+        aggregate.total_bytes_written += result.total_bytes_written;
+        aggregate.total_cpu_cycles += result.total_cpu_cycles;
+
+        for thread_stat in &mut result.thread_stats {
+            thread_stat.node_id = Some(node_id);
+        }
+        aggregate.thread_stats.append(&mut result.thread_stats);
+    }
+
+    if aggregate.total_operations > 0 {
+        let total_latency: u64 = aggregate.thread_stats.iter().map(|t| t.total_latency_ns).sum();
+        aggregate.average_latency_ns = total_latency as f64 / aggregate.total_operations as f64;
+    }
+
+    let seconds = total_wall_clock.as_secs_f64();
+    if seconds > 0.0 {
+        aggregate.operations_per_second = aggregate.total_operations as f64 / seconds;
+        aggregate.read_throughput_mbps = (aggregate.total_bytes_read as f64 / (1024.0 * 1024.0)) / seconds;
+        aggregate.write_throughput_mbps = (aggregate.total_bytes_written as f64 / (1024.0 * 1024.0)) / seconds;
+    }
+
+    aggregate
+}
@@ -1,17 +1,101 @@
+use crate::cache::PageCache;
 use crate::common::*;
 use anyhow::Result;
-use std::fs::OpenOptions;
-use std::os::unix::fs::OpenOptionsExt;
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, IoSlice, IoSliceMut, Write};
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
+/// How many recently-touched offsets each thread's reuse pool remembers.
+const REUSE_POOL_CAPACITY: usize = 64;
+
+/// Safety valve on the `cas_fail_rate` retry loop so a rate close to 1.0
+/// can't spin a thread forever.
+const MAX_CAS_RETRIES: u64 = 10_000;
+
+/// Pick the next address for an `AddressMode::ReusePool` operation: with
+/// probability `reuse_rate`, reuse an offset already in a pool (itself drawn
+/// from another thread's pool with probability `cross_thread_reuse_rate`);
+/// otherwise draw a fresh random offset within the working set and push it
+/// into this thread's own pool, evicting the oldest entry once full.
+fn draw_reuse_address(
+    rng: &mut ChaCha20Rng,
+    thread_id: usize,
+    pools: &[Mutex<VecDeque<u64>>],
+    reuse_rate: f64,
+    cross_thread_reuse_rate: f64,
+    working_set_base: u64,
+    working_set_size: u64,
+) -> u64 {
+    if rng.gen::<f64>() < reuse_rate {
+        let source = if pools.len() > 1 && rng.gen::<f64>() < cross_thread_reuse_rate {
+            let mut other = rng.gen_range(0..pools.len());
+            while other == thread_id {
+                other = rng.gen_range(0..pools.len());
+            }
+            other
+        } else {
+            thread_id
+        };
+
+        let pool = pools[source].lock().unwrap();
+        if !pool.is_empty() {
+            return pool[rng.gen_range(0..pool.len())];
+        }
+    }
+
+    let offset = rng.gen_range(0..working_set_size.max(1));
+    let address = working_set_base + offset;
+
+    let mut pool = pools[thread_id].lock().unwrap();
+    if pool.len() >= REUSE_POOL_CAPACITY {
+        pool.pop_front();
+    }
+    pool.push_back(address);
+    address
+}
+
+/// `O_DIRECT` requires the offset, length, and buffer address of every
+/// transfer to be a multiple of this (true of every mainstream block device).
+const O_DIRECT_ALIGN: u64 = 4096;
+
+fn check_direct_io_aligned(label: &str, value: u64) -> Result<()> {
+    if value % O_DIRECT_ALIGN != 0 {
+        anyhow::bail!("O_DIRECT {} {} is not {}-byte aligned", label, value, O_DIRECT_ALIGN);
+    }
+    Ok(())
+}
+
+/// How `base_address` was obtained, and therefore how `Drop` must release it.
+#[derive(PartialEq, Eq)]
+enum Backing {
+    /// The device itself, mmap'd in (`MAP_SHARED`); released with `munmap`.
+    Mmap,
+    /// A malloc'd staging buffer fronting a non-mmap device's `O_DIRECT`
+    /// positional I/O; released with `dealloc`.
+    Malloc,
+    /// Plain malloc'd system memory, no device backing it; released with
+    /// `dealloc`.
+    SystemAlloc,
+}
+
 /// Simple memory manager
 pub struct MemoryManager {
     base_address: *mut u8,
     size: u64,
-    is_device: bool,
+    backing: Backing,
+    /// Kept open for the lifetime of the manager so the non-mmap device path
+    /// can issue real positional I/O against it via an `IoEngine`.
+    device_file: Option<File>,
 }
 
 impl MemoryManager {
@@ -35,17 +119,18 @@ impl MemoryManager {
         Ok(Self {
             base_address,
             size,
-            is_device: false,
+            backing: Backing::SystemAlloc,
+            device_file: None,
         })
     }
-    
+
     pub fn new_device_memory(device_path: &str, size: u64, use_mmap: bool) -> Result<Self> {
         let file = OpenOptions::new()
             .read(true)
             .write(true)
             .custom_flags(libc::O_DIRECT)
             .open(device_path)?;
-            
+
         let base_address = if use_mmap {
             unsafe {
                 let addr = libc::mmap(
@@ -53,14 +138,14 @@ impl MemoryManager {
                     size as libc::size_t,
                     libc::PROT_READ | libc::PROT_WRITE,
                     libc::MAP_SHARED,
-                    std::os::unix::io::AsRawFd::as_raw_fd(&file),
+                    file.as_raw_fd(),
                     0,
                 );
-                
+
                 if addr == libc::MAP_FAILED {
                     anyhow::bail!("Failed to mmap device");
                 }
-                
+
                 addr as *mut u8
             }
         } else {
@@ -68,15 +153,43 @@ impl MemoryManager {
             let layout = std::alloc::Layout::from_size_align(size as usize, 4096)?;
             unsafe { std::alloc::alloc(layout) }
         };
-        
+
         if base_address.is_null() {
             anyhow::bail!("Failed to allocate/map device memory");
         }
-        
+
         Ok(Self {
             base_address,
             size,
-            is_device: true,
+            backing: if use_mmap { Backing::Mmap } else { Backing::Malloc },
+            device_file: Some(file),
+        })
+    }
+
+    /// Whether this manager owns an open device file it can issue positional
+    /// I/O against -- true only for the non-mmap device path, since the mmap
+    /// path is already touching the device through the mapping.
+    pub fn is_device_file_backed(&self) -> bool {
+        self.backing == Backing::Malloc && self.device_file.is_some()
+    }
+
+    fn is_mmap(&self) -> bool {
+        self.backing == Backing::Mmap
+    }
+
+    /// Build the `IoEngine` selected for this run's device path. Only
+    /// meaningful for a device opened without mmap -- that's the path that
+    /// actually issues positional reads/writes against `device_file`.
+    pub fn io_engine(&self, kind: IoEngineKind, queue_depth: usize) -> Result<Box<dyn IoEngine>> {
+        let fd = self
+            .device_file
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("io_engine requires device memory"))?
+            .as_raw_fd();
+
+        Ok(match kind {
+            IoEngineKind::Sync => Box::new(SyncIoEngine::new(fd, queue_depth)),
+            IoEngineKind::IoUring => Box::new(IoUringIoEngine::new(fd, queue_depth)?),
         })
     }
     
@@ -84,10 +197,23 @@ impl MemoryManager {
         if address + size as u64 > self.size {
             anyhow::bail!("Read beyond memory bounds");
         }
-        
+
+        if let Some(file) = &self.device_file {
+            if !self.is_mmap() {
+                check_direct_io_aligned("offset", address)?;
+                check_direct_io_aligned("length", size as u64)?;
+
+                let start = Instant::now();
+                let mut buffer = vec![0u8; size];
+                file.read_at(&mut buffer, address)?;
+                std::hint::black_box(buffer);
+                return Ok(start.elapsed());
+            }
+        }
+
         let start = Instant::now();
         let mut buffer = vec![0u8; size];
-        
+
         unsafe {
             ptr::copy_nonoverlapping(
                 self.base_address.add(address as usize),
@@ -95,21 +221,33 @@ impl MemoryManager {
                 size,
             );
         }
-        
+
         // Prevent optimization
         std::hint::black_box(buffer);
-        
+
         Ok(start.elapsed())
     }
-    
+
     pub fn execute_write(&self, address: u64, size: usize) -> Result<Duration> {
         if address + size as u64 > self.size {
             anyhow::bail!("Write beyond memory bounds");
         }
-        
+
+        if let Some(file) = &self.device_file {
+            if !self.is_mmap() {
+                check_direct_io_aligned("offset", address)?;
+                check_direct_io_aligned("length", size as u64)?;
+
+                let start = Instant::now();
+                let buffer = vec![0xAAu8; size];
+                file.write_at(&buffer, address)?;
+                return Ok(start.elapsed());
+            }
+        }
+
         let start = Instant::now();
         let buffer = vec![0xAA; size]; // Write pattern
-        
+
         unsafe {
             ptr::copy_nonoverlapping(
                 buffer.as_ptr(),
@@ -117,10 +255,144 @@ impl MemoryManager {
                 size,
             );
         }
-        
+
         Ok(start.elapsed())
     }
-    
+
+    /// Scatter a single contiguous `O_DIRECT` read starting at `address`
+    /// across `fragments` in one `preadv` call, instead of one `size`-byte
+    /// transfer into a single buffer.
+    pub fn execute_read_vectored(&self, address: u64, fragments: &[IoFragment]) -> Result<Duration> {
+        let file = self.direct_io_file()?;
+        check_direct_io_aligned("offset", address)?;
+
+        let mut buffers: Vec<Vec<u8>> = Vec::with_capacity(fragments.len());
+        for frag in fragments {
+            check_direct_io_aligned("length", frag.length as u64)?;
+            buffers.push(vec![0u8; frag.length]);
+        }
+
+        let start = Instant::now();
+        let mut slices: Vec<IoSliceMut> = buffers.iter_mut().map(|b| IoSliceMut::new(b)).collect();
+        let transferred = preadv_at(file.as_raw_fd(), &mut slices, address)?;
+
+        let expected: usize = fragments.iter().map(|f| f.length).sum();
+        if transferred != expected {
+            anyhow::bail!("short preadv: got {} of {} expected bytes", transferred, expected);
+        }
+        std::hint::black_box(buffers);
+        Ok(start.elapsed())
+    }
+
+    /// Gather `fragments` worth of the write pattern into one contiguous
+    /// `O_DIRECT` write starting at `address`, via a single `pwritev` call.
+    pub fn execute_write_vectored(&self, address: u64, fragments: &[IoFragment]) -> Result<Duration> {
+        let file = self.direct_io_file()?;
+        check_direct_io_aligned("offset", address)?;
+
+        let mut buffers: Vec<Vec<u8>> = Vec::with_capacity(fragments.len());
+        for frag in fragments {
+            check_direct_io_aligned("length", frag.length as u64)?;
+            buffers.push(vec![0xAAu8; frag.length]);
+        }
+
+        let start = Instant::now();
+        let slices: Vec<IoSlice> = buffers.iter().map(|b| IoSlice::new(b)).collect();
+        let transferred = pwritev_at(file.as_raw_fd(), &slices, address)?;
+
+        let expected: usize = fragments.iter().map(|f| f.length).sum();
+        if transferred != expected {
+            anyhow::bail!("short pwritev: wrote {} of {} expected bytes", transferred, expected);
+        }
+        Ok(start.elapsed())
+    }
+
+    /// Read `size` bytes of real data starting at `address`, from the device
+    /// file if this is a non-mmap device, or straight out of `base_address`
+    /// otherwise. Used by `cache::PageCache` to fill a page on a miss.
+    pub fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+        if address + size as u64 > self.size {
+            anyhow::bail!("Read beyond memory bounds");
+        }
+
+        if let Some(file) = &self.device_file {
+            if !self.is_mmap() {
+                let mut buffer = vec![0u8; size];
+                file.read_at(&mut buffer, address)?;
+                return Ok(buffer);
+            }
+        }
+
+        let mut buffer = vec![0u8; size];
+        unsafe {
+            ptr::copy_nonoverlapping(self.base_address.add(address as usize), buffer.as_mut_ptr(), size);
+        }
+        Ok(buffer)
+    }
+
+    /// Write `data` starting at `address`, mirroring `read_bytes`. Used by
+    /// `cache::PageCache` to write back a dirty page on eviction/finalize.
+    pub fn write_bytes(&self, address: u64, data: &[u8]) -> Result<()> {
+        if address + data.len() as u64 > self.size {
+            anyhow::bail!("Write beyond memory bounds");
+        }
+
+        if let Some(file) = &self.device_file {
+            if !self.is_mmap() {
+                file.write_at(data, address)?;
+                return Ok(());
+            }
+        }
+
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr(), self.base_address.add(address as usize), data.len());
+        }
+        Ok(())
+    }
+
+    /// Borrow the 8 bytes at `address` as an `AtomicU64`, for RMW ops on a
+    /// region mapped `MAP_SHARED` (or plain system memory). `address` must be
+    /// 8-byte aligned, same as the hardware requires for a lock-free atomic.
+    fn atomic_at(&self, address: u64) -> Result<&std::sync::atomic::AtomicU64> {
+        if address + 8 > self.size {
+            anyhow::bail!("Atomic op beyond memory bounds");
+        }
+        if address % 8 != 0 {
+            anyhow::bail!("Atomic address {} must be 8-byte aligned", address);
+        }
+        Ok(unsafe { &*(self.base_address.add(address as usize) as *const std::sync::atomic::AtomicU64) })
+    }
+
+    /// Atomically add `value` to the 8 bytes at `address`.
+    pub fn execute_fetch_add(&self, address: u64, value: u64) -> Result<Duration> {
+        let atomic = self.atomic_at(address)?;
+        let start = Instant::now();
+        atomic.fetch_add(value, std::sync::atomic::Ordering::SeqCst);
+        Ok(start.elapsed())
+    }
+
+    /// Attempt `compare_exchange_weak(expected, new)` at `address`, returning
+    /// the transfer latency and whether the hardware CAS actually succeeded.
+    pub fn execute_compare_exchange(&self, address: u64, expected: u64, new: u64) -> Result<(Duration, bool)> {
+        let atomic = self.atomic_at(address)?;
+        let start = Instant::now();
+        let succeeded = atomic
+            .compare_exchange_weak(expected, new, std::sync::atomic::Ordering::SeqCst, std::sync::atomic::Ordering::SeqCst)
+            .is_ok();
+        Ok((start.elapsed(), succeeded))
+    }
+
+    /// The open device file for the non-mmap `O_DIRECT` path, or an error if
+    /// this manager isn't backed by one (system memory, or an mmap'd device).
+    fn direct_io_file(&self) -> Result<&File> {
+        if self.is_mmap() {
+            anyhow::bail!("vectored positional I/O is not available on an mmap'd device region");
+        }
+        self.device_file
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("vectored positional I/O requires device memory"))
+    }
+
     pub fn execute_cpu(&self, cycles: u64) -> Result<Duration> {
         let start = Instant::now();
         
@@ -139,17 +411,17 @@ impl MemoryManager {
 
 impl Drop for MemoryManager {
     fn drop(&mut self) {
-        if !self.base_address.is_null() {
-            if self.is_device {
-                unsafe {
-                    libc::munmap(self.base_address as *mut libc::c_void, self.size as libc::size_t);
-                }
-            } else {
-                unsafe {
-                    let layout = std::alloc::Layout::from_size_align_unchecked(self.size as usize, 4096);
-                    std::alloc::dealloc(self.base_address, layout);
-                }
-            }
+        if self.base_address.is_null() {
+            return;
+        }
+        match self.backing {
+            Backing::Mmap => unsafe {
+                libc::munmap(self.base_address as *mut libc::c_void, self.size as libc::size_t);
+            },
+            Backing::Malloc | Backing::SystemAlloc => unsafe {
+                let layout = std::alloc::Layout::from_size_align_unchecked(self.size as usize, 4096);
+                std::alloc::dealloc(self.base_address, layout);
+            },
         }
     }
 }
@@ -157,74 +429,626 @@ impl Drop for MemoryManager {
 unsafe impl Send for MemoryManager {}
 unsafe impl Sync for MemoryManager {}
 
+/// `preadv(2)` at a fixed file offset: fills `bufs` in order from one
+/// contiguous run of bytes starting at `offset`, returning the total bytes
+/// read. Relies on `IoSliceMut` sharing `iovec`'s layout, same as the
+/// standard library's own `Read`/`Write` vectored impls do internally.
+fn preadv_at(fd: RawFd, bufs: &mut [IoSliceMut], offset: u64) -> Result<usize> {
+    let n = unsafe {
+        libc::preadv(
+            fd,
+            bufs.as_mut_ptr() as *mut libc::iovec,
+            bufs.len() as libc::c_int,
+            offset as libc::off_t,
+        )
+    };
+    if n < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(n as usize)
+}
+
+/// `pwritev(2)` counterpart to `preadv_at`.
+fn pwritev_at(fd: RawFd, bufs: &[IoSlice], offset: u64) -> Result<usize> {
+    let n = unsafe {
+        libc::pwritev(
+            fd,
+            bufs.as_ptr() as *const libc::iovec,
+            bufs.len() as libc::c_int,
+            offset as libc::off_t,
+        )
+    };
+    if n < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(n as usize)
+}
+
+/// A completed I/O request: the `user_data` tag it was submitted with, and
+/// the raw syscall result (bytes transferred, or `-errno`).
+pub type IoCompletion = (u64, i32);
+
+/// Abstraction over how `PatternExecutor` issues device I/O, so a thread can
+/// keep `queue_depth` requests outstanding instead of blocking on each op.
+/// Latency is measured from `submit_*` to the matching completion.
+pub trait IoEngine: Send {
+    /// Queue a read of `len` bytes at `offset` into `buf`, tagged `user_data`.
+    ///
+    /// # Safety
+    /// `buf` must stay valid and exclusively borrowed until the matching
+    /// completion is observed via `poll_completions`.
+    unsafe fn submit_read(&mut self, buf: *mut u8, len: usize, offset: u64, user_data: u64) -> Result<()>;
+
+    /// Queue a write of `len` bytes from `buf` at `offset`, tagged `user_data`.
+    ///
+    /// # Safety
+    /// `buf` must stay valid and not be mutated until the matching completion
+    /// is observed via `poll_completions`.
+    unsafe fn submit_write(&mut self, buf: *const u8, len: usize, offset: u64, user_data: u64) -> Result<()>;
+
+    /// Wait until at least `min_complete` requests (and at least one) have
+    /// completed, returning every completion observed. The wait is not capped
+    /// at the number of requests actually outstanding.
+    fn poll_completions(&mut self, min_complete: usize) -> Result<Vec<IoCompletion>>;
+
+    fn queue_depth(&self) -> usize;
+}
+
+/// Synchronous `pread`/`pwrite` engine: each submit performs the I/O inline
+/// and queues its result, so `poll_completions` never actually blocks. Serves
+/// as the baseline engine and the fallback when io_uring isn't available.
+pub struct SyncIoEngine {
+    fd: RawFd,
+    queue_depth: usize,
+    completed: VecDeque<IoCompletion>,
+}
+
+impl SyncIoEngine {
+    pub fn new(fd: RawFd, queue_depth: usize) -> Self {
+        Self { fd, queue_depth, completed: VecDeque::new() }
+    }
+
+    fn file(&self) -> std::mem::ManuallyDrop<File> {
+        // Borrow the fd without taking ownership of it (and so without closing
+        // it when this wrapper drops).
+        use std::os::unix::io::FromRawFd;
+        std::mem::ManuallyDrop::new(unsafe { File::from_raw_fd(self.fd) })
+    }
+}
+
+impl IoEngine for SyncIoEngine {
+    unsafe fn submit_read(&mut self, buf: *mut u8, len: usize, offset: u64, user_data: u64) -> Result<()> {
+        let slice = std::slice::from_raw_parts_mut(buf, len);
+        let result = self.file().read_at(slice, offset);
+        self.completed.push_back((user_data, result.map(|n| n as i32).unwrap_or(-1)));
+        Ok(())
+    }
+
+    unsafe fn submit_write(&mut self, buf: *const u8, len: usize, offset: u64, user_data: u64) -> Result<()> {
+        let slice = std::slice::from_raw_parts(buf, len);
+        let result = self.file().write_at(slice, offset);
+        self.completed.push_back((user_data, result.map(|n| n as i32).unwrap_or(-1)));
+        Ok(())
+    }
+
+    fn poll_completions(&mut self, _min_complete: usize) -> Result<Vec<IoCompletion>> {
+        Ok(self.completed.drain(..).collect())
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+}
+
+/// Asynchronous io_uring engine. Keeps a submission queue of in-flight
+/// requests up to `queue_depth` deep and drains completions in batches,
+/// which is what lets a thread saturate device bandwidth instead of
+/// serializing on one request at a time.
+pub struct IoUringIoEngine {
+    ring: io_uring::IoUring,
+    fd: RawFd,
+    queue_depth: usize,
+}
+
+impl IoUringIoEngine {
+    pub fn new(fd: RawFd, queue_depth: usize) -> Result<Self> {
+        let ring = io_uring::IoUring::new(queue_depth.max(1) as u32)?;
+        Ok(Self { ring, fd, queue_depth })
+    }
+}
+
+impl IoEngine for IoUringIoEngine {
+    unsafe fn submit_read(&mut self, buf: *mut u8, len: usize, offset: u64, user_data: u64) -> Result<()> {
+        let entry = io_uring::opcode::Read::new(io_uring::types::Fd(self.fd), buf, len as u32)
+            .offset(offset)
+            .build()
+            .user_data(user_data);
+
+        while unsafe { self.ring.submission().push(&entry) }.is_err() {
+            // Submission queue is full; push out what's pending and retry.
+            self.ring.submit()?;
+        }
+        Ok(())
+    }
+
+    unsafe fn submit_write(&mut self, buf: *const u8, len: usize, offset: u64, user_data: u64) -> Result<()> {
+        let entry = io_uring::opcode::Write::new(io_uring::types::Fd(self.fd), buf, len as u32)
+            .offset(offset)
+            .build()
+            .user_data(user_data);
+
+        while unsafe { self.ring.submission().push(&entry) }.is_err() {
+            self.ring.submit()?;
+        }
+        Ok(())
+    }
+
+    fn poll_completions(&mut self, min_complete: usize) -> Result<Vec<IoCompletion>> {
+        self.ring.submit_and_wait(min_complete.max(1))?;
+
+        let completions = self
+            .ring
+            .completion()
+            .map(|cqe| (cqe.user_data(), cqe.result()))
+            .collect();
+        Ok(completions)
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+}
+
+/// Number of linear sub-buckets per power-of-two octave. 8 sub-buckets gives
+/// ~12% worst-case bucketing error, which is plenty for a report that rounds
+/// to a percentile anyway.
+const HISTOGRAM_PRECISION_BITS: u32 = 3;
+const HISTOGRAM_SUBBUCKETS: usize = 1 << HISTOGRAM_PRECISION_BITS;
+/// Large enough to cover latencies up to 2^63 ns without growing; in practice
+/// only the first few dozen octaves are ever touched.
+const HISTOGRAM_NUM_BUCKETS: usize = HISTOGRAM_SUBBUCKETS * (64 - HISTOGRAM_PRECISION_BITS as usize + 1);
+
+/// A log-linear (HDR-style) latency histogram: a fixed `Vec<u64>` of bucket
+/// counts, one `u64` increment per `record` call with no allocation on the hot
+/// path. Splitting each power-of-two magnitude into `HISTOGRAM_SUBBUCKETS`
+/// linear sub-buckets keeps relative error bounded and the whole table at a
+/// few KB regardless of how many operations are recorded.
+#[derive(Clone)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self { buckets: vec![0u64; HISTOGRAM_NUM_BUCKETS] }
+    }
+
+    /// Map a latency in nanoseconds to its bucket index.
+    fn bucket_index(v: u64) -> usize {
+        let k = HISTOGRAM_PRECISION_BITS;
+        if v < (1 << k) {
+            return v as usize;
+        }
+        let msb = 63 - v.leading_zeros();
+        let shift = msb - k;
+        (((1u64 << k) * (shift as u64 + 1)) + ((v >> shift) & ((1 << k) - 1))) as usize
+    }
+
+    /// Reconstruct a bucket's representative (lower-bound) value.
+    fn bucket_value(idx: usize) -> u64 {
+        let k = HISTOGRAM_PRECISION_BITS;
+        let s = 1usize << k;
+        if idx < s {
+            return idx as u64;
+        }
+        let shift = ((idx - s) / s) as u32 + k;
+        let sub = ((idx - s) % s) as u64;
+        (1u64 << shift) | (sub << (shift - k))
+    }
+
+    pub fn record(&mut self, latency_ns: u64) {
+        let idx = Self::bucket_index(latency_ns).min(self.buckets.len() - 1);
+        self.buckets[idx] += 1;
+    }
+
+    fn merge(&mut self, other: &LatencyHistogram) {
+        for (a, b) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *a += b;
+        }
+    }
+
+    /// Percentile `p` in `(0, 100]`: walk cumulative counts until they clear
+    /// `ceil(p/100 * total)`, and return that bucket's representative value.
+    pub fn percentile(&self, p: f64) -> u64 {
+        let total: u64 = self.buckets.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+        let target = ((p / 100.0) * total as f64).ceil() as u64;
+        let mut running = 0u64;
+        for (idx, count) in self.buckets.iter().enumerate() {
+            running += count;
+            if running >= target {
+                return Self::bucket_value(idx);
+            }
+        }
+        Self::bucket_value(self.buckets.len() - 1)
+    }
+
+    pub fn percentiles(&self) -> LatencyPercentiles {
+        LatencyPercentiles {
+            p50_ns: self.percentile(50.0),
+            p90_ns: self.percentile(90.0),
+            p99_ns: self.percentile(99.0),
+            p999_ns: self.percentile(99.9),
+        }
+    }
+}
+
+/// Per-thread histograms, kept separate from `ThreadStats` so the hot path
+/// only ever touches a plain `Vec<u64>` bump and `ExecutionResults` stays
+/// cheap to clone on every `finalize` call.
+#[derive(Clone)]
+struct ThreadHistograms {
+    read: LatencyHistogram,
+    write: LatencyHistogram,
+    cpu: LatencyHistogram,
+}
+
+impl ThreadHistograms {
+    fn new() -> Self {
+        Self {
+            read: LatencyHistogram::new(),
+            write: LatencyHistogram::new(),
+            cpu: LatencyHistogram::new(),
+        }
+    }
+}
+
 /// Metrics collector
 #[derive(Clone)]
 pub struct MetricsCollector {
     stats: Arc<Mutex<ExecutionResults>>,
+    histograms: Arc<Mutex<Vec<ThreadHistograms>>>,
 }
 
 impl MetricsCollector {
     pub fn new(num_threads: usize) -> Self {
         let mut results = ExecutionResults::default();
         results.thread_stats = vec![ThreadStats::default(); num_threads];
-        
+
         Self {
             stats: Arc::new(Mutex::new(results)),
+            histograms: Arc::new(Mutex::new(vec![ThreadHistograms::new(); num_threads])),
         }
     }
-    
-    pub fn record_operation(&self, thread_id: usize, op: &Operation, latency: Duration) {
+
+    pub fn record_operation(&self, thread_id: usize, op: &ThreadOperation, latency: Duration) {
         let mut stats = self.stats.lock().unwrap();
         let thread_stats = &mut stats.thread_stats[thread_id];
-        
-        thread_stats.thread_id = thread_id;
+
+        thread_stats.thread_id = thread_id as u32;
         thread_stats.operations_completed += 1;
-        
+
         let latency_ns = latency.as_nanos() as u64;
         thread_stats.total_latency_ns += latency_ns;
-        
+
         if thread_stats.min_latency_ns == 0 || latency_ns < thread_stats.min_latency_ns {
             thread_stats.min_latency_ns = latency_ns;
         }
         if latency_ns > thread_stats.max_latency_ns {
             thread_stats.max_latency_ns = latency_ns;
         }
-        
+
+        let mut histograms = self.histograms.lock().unwrap();
+        let thread_histograms = &mut histograms[thread_id];
+
         match op.op_type {
             OpType::Read => {
                 thread_stats.bytes_read += op.size.unwrap_or(0) as u64;
+                thread_histograms.read.record(latency_ns);
             },
             OpType::Write => {
                 thread_stats.bytes_written += op.size.unwrap_or(0) as u64;
+                thread_histograms.write.record(latency_ns);
             },
             OpType::Cpu => {
                 thread_stats.cpu_cycles_executed += op.cpu_cycles.unwrap_or(0);
+                thread_histograms.cpu.record(latency_ns);
             },
+            OpType::FetchAdd | OpType::CompareExchange => {},
         }
     }
-    
+
+    /// Add to a thread's retry count for `CompareExchange` ops that didn't
+    /// succeed on the first attempt, whether from real contention or an
+    /// injected `cas_fail_rate` retry.
+    pub fn record_atomic_retries(&self, thread_id: usize, retries: u64) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.thread_stats[thread_id].atomic_retries += retries;
+    }
+
+    /// Cumulative (operations, bytes_read, bytes_written, total_latency_ns)
+    /// across all threads so far. Cheap enough to call on every
+    /// `metrics_interval_ms` tick without disturbing worker threads.
+    pub fn cumulative_totals(&self) -> (u64, u64, u64, u64) {
+        let stats = self.stats.lock().unwrap();
+        (
+            stats.thread_stats.iter().map(|t| t.operations_completed).sum(),
+            stats.thread_stats.iter().map(|t| t.bytes_read).sum(),
+            stats.thread_stats.iter().map(|t| t.bytes_written).sum(),
+            stats.thread_stats.iter().map(|t| t.total_latency_ns).sum(),
+        )
+    }
+
     pub fn finalize(&self, total_duration: Duration) -> ExecutionResults {
         let mut stats = self.stats.lock().unwrap();
-        
+
         stats.total_duration_ns = total_duration.as_nanos() as u64;
         stats.total_operations = stats.thread_stats.iter().map(|t| t.operations_completed).sum();
         stats.total_bytes_read = stats.thread_stats.iter().map(|t| t.bytes_read).sum();
         stats.total_bytes_written = stats.thread_stats.iter().map(|t| t.bytes_written).sum();
         stats.total_cpu_cycles = stats.thread_stats.iter().map(|t| t.cpu_cycles_executed).sum();
-        
+
         if stats.total_operations > 0 {
             let total_latency: u64 = stats.thread_stats.iter().map(|t| t.total_latency_ns).sum();
             stats.average_latency_ns = total_latency as f64 / stats.total_operations as f64;
         }
-        
+
         let seconds = total_duration.as_secs_f64();
         if seconds > 0.0 {
             stats.read_throughput_mbps = (stats.total_bytes_read as f64 / (1024.0 * 1024.0)) / seconds;
             stats.write_throughput_mbps = (stats.total_bytes_written as f64 / (1024.0 * 1024.0)) / seconds;
         }
-        
+
+        let histograms = self.histograms.lock().unwrap();
+        let mut merged = ThreadHistograms::new();
+        for (thread_stats, thread_histograms) in stats.thread_stats.iter_mut().zip(histograms.iter()) {
+            thread_stats.read_latency_percentiles = thread_histograms.read.percentiles();
+            thread_stats.write_latency_percentiles = thread_histograms.write.percentiles();
+            thread_stats.cpu_latency_percentiles = thread_histograms.cpu.percentiles();
+
+            merged.read.merge(&thread_histograms.read);
+            merged.write.merge(&thread_histograms.write);
+            merged.cpu.merge(&thread_histograms.cpu);
+        }
+        stats.read_latency_percentiles = merged.read.percentiles();
+        stats.write_latency_percentiles = merged.write.percentiles();
+        stats.cpu_latency_percentiles = merged.cpu.percentiles();
+
         (*stats).clone()
     }
+
+    /// A live `ExecutionResults` snapshot for the control socket's `stats`
+    /// command, computed the same way `finalize` derives its totals but
+    /// without disturbing the run in progress (latency percentiles stay
+    /// whatever they were at the last `finalize` call, since histograms
+    /// aren't merged mid-run).
+    pub fn snapshot_results(&self, elapsed: Duration) -> ExecutionResults {
+        let mut stats = self.stats.lock().unwrap().clone();
+
+        stats.total_duration_ns = elapsed.as_nanos() as u64;
+        stats.total_operations = stats.thread_stats.iter().map(|t| t.operations_completed).sum();
+        stats.total_bytes_read = stats.thread_stats.iter().map(|t| t.bytes_read).sum();
+        stats.total_bytes_written = stats.thread_stats.iter().map(|t| t.bytes_written).sum();
+        stats.total_cpu_cycles = stats.thread_stats.iter().map(|t| t.cpu_cycles_executed).sum();
+
+        if stats.total_operations > 0 {
+            let total_latency: u64 = stats.thread_stats.iter().map(|t| t.total_latency_ns).sum();
+            stats.average_latency_ns = total_latency as f64 / stats.total_operations as f64;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            stats.read_throughput_mbps = (stats.total_bytes_read as f64 / (1024.0 * 1024.0)) / seconds;
+            stats.write_throughput_mbps = (stats.total_bytes_written as f64 / (1024.0 * 1024.0)) / seconds;
+        }
+
+        stats
+    }
+}
+
+/// Shared state a running `PatternExecutor` can be steered through over its
+/// `--control-sock`: pause/resume and a live rate-limit override. Workers
+/// consult this between operations instead of the executor stopping them.
+pub struct ControlState {
+    paused: AtomicBool,
+    /// Bytes/sec cap applied by `throttle`; 0 means unlimited.
+    rate_limit_bytes_per_sec: AtomicU64,
+}
+
+impl ControlState {
+    fn new() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            rate_limit_bytes_per_sec: AtomicU64::new(0),
+        }
+    }
+
+    fn wait_while_paused(&self) {
+        while self.paused.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Best-effort per-operation throttle (not a true token bucket): sleep
+    /// long enough that `bytes` alone would respect the current rate cap.
+    fn throttle(&self, bytes: u64) {
+        let rate = self.rate_limit_bytes_per_sec.load(Ordering::Relaxed);
+        if rate == 0 || bytes == 0 {
+            return;
+        }
+        let seconds = bytes as f64 / rate as f64;
+        if seconds > 0.0 {
+            thread::sleep(Duration::from_secs_f64(seconds));
+        }
+    }
+
+    fn set_rate_limit(&self, bytes_per_sec: Option<u64>) {
+        self.rate_limit_bytes_per_sec.store(bytes_per_sec.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+/// Accept line-delimited commands on `sock_path` until `stop_rx` fires:
+/// `pause`, `resume`, `set-rate <bandwidth-string>`, and `stats`. Polls the
+/// listener fd alongside the stop channel so it can shut down promptly
+/// instead of blocking forever in `accept`.
+fn run_control_socket(
+    sock_path: String,
+    control: Arc<ControlState>,
+    metrics: MetricsCollector,
+    start_time: Instant,
+    stop_rx: std::sync::mpsc::Receiver<()>,
+) {
+    let _ = std::fs::remove_file(&sock_path);
+    let listener = match UnixListener::bind(&sock_path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("control socket: failed to bind {sock_path}: {e}");
+            return;
+        }
+    };
+    listener.set_nonblocking(true).expect("failed to set control socket non-blocking");
+    let listener_fd = listener.as_raw_fd();
+
+    loop {
+        if stop_rx.try_recv().is_ok() {
+            break;
+        }
+
+        let mut pfd = libc::pollfd { fd: listener_fd, events: libc::POLLIN, revents: 0 };
+        let ready = unsafe { libc::poll(&mut pfd, 1, 100) };
+        if ready <= 0 {
+            continue;
+        }
+
+        if let Ok((stream, _)) = listener.accept() {
+            handle_control_connection(stream, &control, &metrics, start_time, &stop_rx);
+        }
+    }
+
+    let _ = std::fs::remove_file(&sock_path);
+}
+
+/// Serve one accepted control connection until it closes or `stop_rx`
+/// fires. The stream is polled non-blockingly (like the listener above)
+/// rather than blocking in `BufRead::lines()`, so a client left attached
+/// (e.g. a monitoring tool that never disconnects) can't keep this thread
+/// alive past the run's own shutdown.
+fn handle_control_connection(
+    stream: UnixStream,
+    control: &ControlState,
+    metrics: &MetricsCollector,
+    start_time: Instant,
+    stop_rx: &std::sync::mpsc::Receiver<()>,
+) {
+    if stream.set_nonblocking(true).is_err() {
+        return;
+    }
+    let fd = stream.as_raw_fd();
+    let mut writer = match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    loop {
+        if stop_rx.try_recv().is_ok() {
+            break;
+        }
+
+        let mut pfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+        let ready = unsafe { libc::poll(&mut pfd, 1, 100) };
+        if ready <= 0 {
+            continue;
+        }
+
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => break, // client closed its end
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(_) => break,
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let response = match trimmed.split_once(' ').unwrap_or((trimmed, "")) {
+            ("pause", _) => {
+                control.paused.store(true, Ordering::Relaxed);
+                "ok\n".to_string()
+            }
+            ("resume", _) => {
+                control.paused.store(false, Ordering::Relaxed);
+                "ok\n".to_string()
+            }
+            ("set-rate", bandwidth) => match parse_bandwidth_string(bandwidth.trim()) {
+                Ok(rate) => {
+                    control.set_rate_limit(rate);
+                    "ok\n".to_string()
+                }
+                Err(e) => format!("error: {e}\n"),
+            },
+            ("stats", _) => {
+                let results = metrics.snapshot_results(start_time.elapsed());
+                match serde_json::to_string(&results) {
+                    Ok(json) => format!("{json}\n"),
+                    Err(e) => format!("error: {e}\n"),
+                }
+            }
+            (other, _) => format!("error: unknown command '{other}'\n"),
+        };
+
+        if writer.write_all(response.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+/// Bookkeeping for one outstanding pipelined I/O request: when it was
+/// submitted (so completion can report submit-to-completion latency) and the
+/// buffer it reads into or writes from, kept alive until the engine reports
+/// it done.
+struct InFlightIo {
+    start: Instant,
+    op_type: OpType,
+    size: usize,
+    _buf: Box<[u8]>,
+}
+
+/// Poll for one or more completions and record their latency, removing each
+/// from `in_flight`. Used both to keep a thread's queue depth bounded while
+/// submitting and to drain the tail once a thread is done issuing requests.
+fn drain_one_completion(
+    engine: &mut dyn IoEngine,
+    in_flight: &mut HashMap<u64, InFlightIo>,
+    metrics: &MetricsCollector,
+    thread_id: usize,
+) {
+    let Ok(completions) = engine.poll_completions(1) else { return };
+    for (user_data, _result) in completions {
+        if let Some(entry) = in_flight.remove(&user_data) {
+            let latency = entry.start.elapsed();
+            let op = ThreadOperation {
+                op_type: entry.op_type,
+                address: None,
+                size: Some(entry.size),
+                cpu_cycles: None,
+                stride: None,
+                iterations: None,
+                think_time_ns: None,
+                fragments: None,
+                address_mode: AddressMode::Fixed,
+                atomic_value: None,
+                atomic_expected: None,
+            };
+            metrics.record_operation(thread_id, &op, latency);
+        }
+    }
 }
 
 /// Simple pattern executor
@@ -232,6 +1056,12 @@ pub struct PatternExecutor {
     memory: Arc<MemoryManager>,
     metrics: MetricsCollector,
     pattern: PatternSpec,
+    /// Write-back DRAM cache fronting device memory, shared by every worker
+    /// thread. `None` when `cache_budget_bytes` is unset or there's no device.
+    cache: Option<Arc<Mutex<PageCache>>>,
+    /// Pause/resume and live rate-limit state, steerable over
+    /// `control_sock_path` while `execute` is running.
+    control: Arc<ControlState>,
 }
 
 impl PatternExecutor {
@@ -241,10 +1071,20 @@ impl PatternExecutor {
         } else {
             Arc::new(MemoryManager::new_system_memory(pattern.memory_size)?)
         };
-        
+
         let metrics = MetricsCollector::new(pattern.num_threads);
-        
-        Ok(Self { memory, metrics, pattern })
+
+        let cache = if pattern.device_path.is_some() {
+            pattern
+                .cache_budget_bytes
+                .map(|budget| Arc::new(Mutex::new(PageCache::new(Arc::clone(&memory), budget))))
+        } else {
+            None
+        };
+
+        let control = Arc::new(ControlState::new());
+
+        Ok(Self { memory, metrics, pattern, cache, control })
     }
     
     pub fn execute(&self) -> Result<ExecutionResults> {
@@ -254,60 +1094,343 @@ impl PatternExecutor {
         println!("Threads: {}", pattern.num_threads);
         
         let start_time = Instant::now();
-        
+
+        // Periodic JSON-lines metrics snapshots, sampled from the same running
+        // counters the worker threads update, without ever pausing them.
+        // The output file is opened up front so a bad/unwritable path fails
+        // `execute()` directly instead of panicking the sampler thread.
+        let metrics_sampler = match pattern.metrics_interval_ms {
+            Some(interval_ms) => {
+                let writer: Box<dyn Write + Send> = match &pattern.metrics_output_path {
+                    Some(path) => Box::new(
+                        File::create(path)
+                            .map_err(|e| anyhow::anyhow!("failed to create metrics output file {path}: {e}"))?,
+                    ),
+                    None => Box::new(std::io::stdout()),
+                };
+                let metrics = self.metrics.clone();
+                let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+
+                let handle = thread::spawn(move || {
+                    let mut writer = writer;
+                    let sampler_start = Instant::now();
+                    let mut prev_elapsed = Duration::ZERO;
+                    let mut prev_bytes_read = 0u64;
+                    let mut prev_bytes_written = 0u64;
+
+                    loop {
+                        let stopping = !matches!(
+                            stop_rx.recv_timeout(Duration::from_millis(interval_ms)),
+                            Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+                        );
+
+                        let elapsed = sampler_start.elapsed();
+                        let (operations, bytes_read, bytes_written, total_latency_ns) = metrics.cumulative_totals();
+
+                        let dt = (elapsed - prev_elapsed).as_secs_f64();
+                        let instantaneous_read_mbps = if dt > 0.0 {
+                            (bytes_read.saturating_sub(prev_bytes_read) as f64 / (1024.0 * 1024.0)) / dt
+                        } else { 0.0 };
+                        let instantaneous_write_mbps = if dt > 0.0 {
+                            (bytes_written.saturating_sub(prev_bytes_written) as f64 / (1024.0 * 1024.0)) / dt
+                        } else { 0.0 };
+
+                        let seconds = elapsed.as_secs_f64();
+                        let snapshot = MetricsSnapshot {
+                            elapsed_seconds: seconds,
+                            cumulative_operations: operations,
+                            cumulative_bytes_read: bytes_read,
+                            cumulative_bytes_written: bytes_written,
+                            average_latency_ns: if operations > 0 { total_latency_ns as f64 / operations as f64 } else { 0.0 },
+                            instantaneous_read_mbps,
+                            instantaneous_write_mbps,
+                            cumulative_read_mbps: if seconds > 0.0 { (bytes_read as f64 / (1024.0 * 1024.0)) / seconds } else { 0.0 },
+                            cumulative_write_mbps: if seconds > 0.0 { (bytes_written as f64 / (1024.0 * 1024.0)) / seconds } else { 0.0 },
+                        };
+
+                        if let Ok(line) = serde_json::to_string(&snapshot) {
+                            let _ = writeln!(writer, "{}", line);
+                            let _ = writer.flush();
+                        }
+
+                        prev_elapsed = elapsed;
+                        prev_bytes_read = bytes_read;
+                        prev_bytes_written = bytes_written;
+
+                        if stopping {
+                            break;
+                        }
+                    }
+                });
+
+                Some((handle, stop_tx))
+            }
+            None => None,
+        };
+
+        // Runtime control socket: pause/resume/set-rate/stats over a Unix
+        // socket, so an in-progress run can be steered without restarting it.
+        let control_server = pattern.control_sock_path.clone().map(|sock_path| {
+            let control = Arc::clone(&self.control);
+            let metrics = self.metrics.clone();
+            let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+            let handle = thread::spawn(move || {
+                run_control_socket(sock_path, control, metrics, start_time, stop_rx);
+            });
+            (handle, stop_tx)
+        });
+
+        // Per-thread `ReusePool` address history, shared so a thread can draw
+        // from another thread's pool for `cross_thread_reuse_rate`.
+        let reuse_pools: Arc<Vec<Mutex<VecDeque<u64>>>> = Arc::new(
+            (0..pattern.num_threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+        );
+
         // Spawn worker threads
         let mut handles = Vec::new();
         for thread_pattern in &pattern.thread_patterns {
             let thread_pattern = thread_pattern.clone();
             let memory = Arc::clone(&self.memory);
             let metrics = self.metrics.clone();
-            
+            let cache = self.cache.clone();
+            let reuse_pools = Arc::clone(&reuse_pools);
+            let control = Arc::clone(&self.control);
+
+            let io_engine_kind = pattern.io_engine;
+            let queue_depth = pattern.queue_depth;
+
             let handle = thread::spawn(move || {
                 let thread_id = thread_pattern.thread_id;
+                let mut rng = ChaCha20Rng::seed_from_u64(thread_pattern.rng_seed.unwrap_or(0));
+                rng.set_stream(thread_id as u64);
+                let reuse_rate = thread_pattern.reuse_rate.unwrap_or(0.0);
+                let cross_thread_reuse_rate = thread_pattern.cross_thread_reuse_rate.unwrap_or(0.0);
+                let cas_fail_rate = thread_pattern.cas_fail_rate.unwrap_or(0.0);
                 let working_set_base = thread_pattern.working_set_base.unwrap_or(0);
                 let working_set_size = thread_pattern.working_set_size.unwrap_or(u64::MAX);
-                
+
+                // The non-mmap device path can pipeline reads/writes through an
+                // IoEngine so the thread keeps `queue_depth` requests in flight
+                // instead of blocking on each one. When a PageCache is fronting
+                // the device instead, it handles reads/writes directly and the
+                // engine pipeline is bypassed entirely.
+                let mut engine = if cache.is_none() && memory.is_device_file_backed() {
+                    Some(memory.io_engine(io_engine_kind, queue_depth).expect("failed to create IoEngine"))
+                } else {
+                    None
+                };
+                let mut in_flight: HashMap<u64, InFlightIo> = HashMap::new();
+                let mut next_user_data: u64 = 0;
+
                 // Repeat pattern if specified
                 let repeat_count = thread_pattern.repeat_pattern.unwrap_or(1);
-                
+
                 for _repeat in 0..repeat_count {
                     for op in &thread_pattern.operations {
                         let iterations = op.iterations.unwrap_or(1);
                         let mut current_address = working_set_base + op.address.unwrap_or(0);
-                        
+
                         for _iter in 0..iterations {
-                            // Execute operation
-                            let latency = match op.op_type {
-                                OpType::Read => {
+                            control.wait_while_paused();
+
+                            if op.address_mode == AddressMode::ReusePool {
+                                current_address = draw_reuse_address(
+                                    &mut rng,
+                                    thread_id,
+                                    &reuse_pools,
+                                    reuse_rate,
+                                    cross_thread_reuse_rate,
+                                    working_set_base,
+                                    working_set_size,
+                                );
+                            }
+
+                            if matches!(op.op_type, OpType::FetchAdd | OpType::CompareExchange) {
+                                // Atomic RMW ops always hit the region directly, bypassing
+                                // both the cache tier and the IoEngine pipeline.
+                                match op.op_type {
+                                    OpType::FetchAdd => {
+                                        if let Ok(latency) = memory.execute_fetch_add(current_address, op.atomic_value.unwrap_or(1)) {
+                                            metrics.record_operation(thread_id, op, latency);
+                                        }
+                                    },
+                                    OpType::CompareExchange => {
+                                        let expected = op.atomic_expected.unwrap_or(0);
+                                        let new = op.atomic_value.unwrap_or(0);
+                                        let mut retries = 0u64;
+                                        loop {
+                                            match memory.execute_compare_exchange(current_address, expected, new) {
+                                                Ok((latency, hw_succeeded)) => {
+                                                    let injected_fail = hw_succeeded && rng.gen::<f64>() < cas_fail_rate;
+                                                    if hw_succeeded && !injected_fail {
+                                                        metrics.record_operation(thread_id, op, latency);
+                                                        metrics.record_atomic_retries(thread_id, retries);
+                                                        break;
+                                                    }
+                                                    retries += 1;
+                                                    if retries >= MAX_CAS_RETRIES {
+                                                        metrics.record_operation(thread_id, op, latency);
+                                                        metrics.record_atomic_retries(thread_id, retries);
+                                                        break;
+                                                    }
+                                                },
+                                                Err(_) => break,
+                                            }
+                                        }
+                                    },
+                                    _ => unreachable!("guarded by the matches! above"),
+                                }
+
+                                if let Some(stride) = op.stride {
+                                    current_address += stride;
+                                }
+                                if let Some(think_time_ns) = op.think_time_ns {
+                                    thread::sleep(Duration::from_nanos(think_time_ns));
+                                }
+                                continue;
+                            }
+
+                            if let Some(fragments) = &op.fragments {
+                                // Scatter/gather ops are always a single synchronous
+                                // preadv/pwritev call, bypassing the IoEngine pipeline.
+                                let result = match op.op_type {
+                                    OpType::Read => memory.execute_read_vectored(current_address, fragments),
+                                    OpType::Write => memory.execute_write_vectored(current_address, fragments),
+                                    OpType::Cpu => memory.execute_cpu(op.cpu_cycles.unwrap_or(1000)),
+                                    OpType::FetchAdd | OpType::CompareExchange => {
+                                        unreachable!("atomic ops are handled before the fragments check")
+                                    },
+                                };
+                                if let Ok(latency) = result {
+                                    metrics.record_operation(thread_id, op, latency);
+                                    if matches!(op.op_type, OpType::Read | OpType::Write) {
+                                        let total: usize = fragments.iter().map(|f| f.length).sum();
+                                        control.throttle(total as u64);
+                                    }
+                                }
+
+                                if let Some(stride) = op.stride {
+                                    current_address += stride;
+                                }
+                                if let Some(think_time_ns) = op.think_time_ns {
+                                    thread::sleep(Duration::from_nanos(think_time_ns));
+                                }
+                                continue;
+                            }
+
+                            if let Some(cache) = &cache {
+                                match op.op_type {
+                                    OpType::Read => {
+                                        let size = op.size.unwrap_or(4096);
+                                        if current_address + size as u64 > working_set_base + working_set_size {
+                                            current_address = working_set_base;
+                                        }
+                                        if let Ok(latency) = cache.lock().unwrap().read(current_address, size) {
+                                            metrics.record_operation(thread_id, op, latency);
+                                            control.throttle(size as u64);
+                                        }
+                                    },
+                                    OpType::Write => {
+                                        let size = op.size.unwrap_or(4096);
+                                        if current_address + size as u64 > working_set_base + working_set_size {
+                                            current_address = working_set_base;
+                                        }
+                                        if let Ok(latency) = cache.lock().unwrap().write(current_address, size) {
+                                            metrics.record_operation(thread_id, op, latency);
+                                            control.throttle(size as u64);
+                                        }
+                                    },
+                                    OpType::Cpu => {
+                                        if let Ok(latency) = memory.execute_cpu(op.cpu_cycles.unwrap_or(1000)) {
+                                            metrics.record_operation(thread_id, op, latency);
+                                        }
+                                    },
+                                    OpType::FetchAdd | OpType::CompareExchange => {
+                                        unreachable!("atomic ops are handled before the cache check")
+                                    },
+                                }
+
+                                if let Some(stride) = op.stride {
+                                    current_address += stride;
+                                }
+                                if let Some(think_time_ns) = op.think_time_ns {
+                                    thread::sleep(Duration::from_nanos(think_time_ns));
+                                }
+                                continue;
+                            }
+
+                            match (op.op_type, engine.as_mut()) {
+                                (OpType::Read, Some(engine)) | (OpType::Write, Some(engine)) => {
+                                    let size = op.size.unwrap_or(4096);
+                                    if current_address + size as u64 > working_set_base + working_set_size {
+                                        current_address = working_set_base;
+                                    }
+
+                                    if in_flight.len() >= engine.queue_depth() {
+                                        drain_one_completion(engine.as_mut(), &mut in_flight, &metrics, thread_id);
+                                    }
+
+                                    let mut buf = vec![0u8; size].into_boxed_slice();
+                                    let user_data = next_user_data;
+                                    next_user_data += 1;
+
+                                    let start = Instant::now();
+                                    let submit_result = unsafe {
+                                        if op.op_type == OpType::Read {
+                                            engine.submit_read(buf.as_mut_ptr(), size, current_address, user_data)
+                                        } else {
+                                            engine.submit_write(buf.as_ptr(), size, current_address, user_data)
+                                        }
+                                    };
+
+                                    if submit_result.is_ok() {
+                                        in_flight.insert(user_data, InFlightIo {
+                                            start,
+                                            op_type: op.op_type,
+                                            size,
+                                            _buf: buf,
+                                        });
+                                    }
+                                },
+                                (OpType::Read, None) => {
                                     let size = op.size.unwrap_or(4096);
-                                    // Ensure address is within working set
                                     if current_address + size as u64 > working_set_base + working_set_size {
                                         current_address = working_set_base;
                                     }
-                                    memory.execute_read(current_address, size)
+                                    if let Ok(latency) = memory.execute_read(current_address, size) {
+                                        metrics.record_operation(thread_id, op, latency);
+                                    }
                                 },
-                                OpType::Write => {
+                                (OpType::Write, None) => {
                                     let size = op.size.unwrap_or(4096);
-                                    // Ensure address is within working set
                                     if current_address + size as u64 > working_set_base + working_set_size {
                                         current_address = working_set_base;
                                     }
-                                    memory.execute_write(current_address, size)
+                                    if let Ok(latency) = memory.execute_write(current_address, size) {
+                                        metrics.record_operation(thread_id, op, latency);
+                                    }
                                 },
-                                OpType::Cpu => {
-                                    memory.execute_cpu(op.cpu_cycles.unwrap_or(1000))
+                                (OpType::Cpu, _) => {
+                                    if let Ok(latency) = memory.execute_cpu(op.cpu_cycles.unwrap_or(1000)) {
+                                        metrics.record_operation(thread_id, op, latency);
+                                    }
+                                },
+                                (OpType::FetchAdd, _) | (OpType::CompareExchange, _) => {
+                                    unreachable!("atomic ops are handled before the engine dispatch")
                                 },
-                            };
-                            
-                            if let Ok(latency) = latency {
-                                metrics.record_operation(thread_id, &op, latency);
                             }
-                            
+
+                            if matches!(op.op_type, OpType::Read | OpType::Write) {
+                                // Approximate: for the pipelined engine path this throttles
+                                // at submission time, not completion.
+                                control.throttle(op.size.unwrap_or(4096) as u64);
+                            }
+
                             // Update address with stride
                             if let Some(stride) = op.stride {
                                 current_address += stride;
                             }
-                            
+
                             // Think time
                             if let Some(think_time_ns) = op.think_time_ns {
                                 thread::sleep(Duration::from_nanos(think_time_ns));
@@ -315,6 +1438,13 @@ impl PatternExecutor {
                         }
                     }
                 }
+
+                // Drain whatever's still outstanding before this thread exits.
+                if let Some(engine) = engine.as_mut() {
+                    while !in_flight.is_empty() {
+                        drain_one_completion(engine.as_mut(), &mut in_flight, &metrics, thread_id);
+                    }
+                }
             });
             
             handles.push(handle);
@@ -324,10 +1454,28 @@ impl PatternExecutor {
         for handle in handles {
             handle.join().expect("Thread panicked");
         }
-        
+
+        // Stop the sampler and let it flush one final, complete snapshot.
+        if let Some((handle, stop_tx)) = metrics_sampler {
+            let _ = stop_tx.send(());
+            handle.join().expect("metrics sampler thread panicked");
+        }
+
+        if let Some((handle, stop_tx)) = control_server {
+            let _ = stop_tx.send(());
+            handle.join().expect("control socket thread panicked");
+        }
+
         let total_duration = start_time.elapsed();
         let mut results = self.metrics.finalize(total_duration);
         results.pattern_name = pattern.name.clone();
+
+        if let Some(cache) = &self.cache {
+            let mut cache = cache.lock().unwrap();
+            cache.finalize()?;
+            results.cache_stats = Some(CacheStats { hits: cache.hits(), misses: cache.misses() });
+        }
+
         Ok(results)
     }
 } 
\ No newline at end of file
@@ -1,8 +1,120 @@
 use crate::common::*;
 use anyhow::Result;
 use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
 use std::collections::HashMap;
 
+/// Below this many operations the per-thread rayon fan-out/join overhead isn't
+/// worth it; generation just runs serially.
+const PARALLEL_OPERATIONS_THRESHOLD: u64 = 100_000;
+
+/// Generate each thread's operations independently (each thread's ops depend
+/// only on its own index and RNG sub-stream) and concatenate them in thread
+/// order. Runs across a rayon thread pool once `operations_count` clears
+/// `PARALLEL_OPERATIONS_THRESHOLD`, or whenever `workload.parallel` pins the
+/// decision explicitly; output is byte-identical either way since each
+/// thread's RNG sub-stream only depends on its own index.
+fn build_per_thread<F>(workload: &WorkloadSpec, threads: u32, operations_count: u64, build: F) -> Vec<Operation>
+where
+    F: Fn(u32) -> Vec<Operation> + Sync,
+{
+    let parallel = workload
+        .parallel
+        .unwrap_or(operations_count >= PARALLEL_OPERATIONS_THRESHOLD);
+
+    if parallel {
+        (0..threads).into_par_iter().map(&build).collect::<Vec<_>>()
+    } else {
+        (0..threads).map(&build).collect::<Vec<_>>()
+    }
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Seed used when a `WorkloadSpec` doesn't pin one down, kept stable so a spec
+/// without `seed` generates the same pattern on every run.
+const DEFAULT_SEED: u64 = 42;
+
+/// Build the seeded RNG for a single thread's stream of decisions. Every thread
+/// draws from the same root seed but a distinct ChaCha stream, so generation is
+/// reproducible across runs/machines and independent of how threads are ordered
+/// or parallelized.
+fn thread_rng(workload: &WorkloadSpec, thread: u32) -> ChaCha20Rng {
+    let seed = workload.seed.unwrap_or(DEFAULT_SEED);
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(thread as u64);
+    rng
+}
+
+/// Default skew for Zipfian/power-law address distributions. 0.99 sits in the
+/// range real database and CXL-tiering traces tend to exhibit.
+const DEFAULT_ZIPF_THETA: f64 = 0.99;
+
+/// Precomputed prefix-sum table for sampling a Zipf-distributed block rank in
+/// `O(log N)` via binary search, built once per generator call and shared
+/// across every draw (and, for callers that build one per thread, every op on
+/// that thread).
+struct ZipfTable {
+    /// `c_k = (sum_{i=1..k} 1/i^theta) / zeta` for k in 1..=n, monotonically
+    /// increasing from >0 up to 1.0.
+    prefix: Vec<f64>,
+}
+
+impl ZipfTable {
+    fn new(n: u64, theta: f64) -> Self {
+        let mut prefix = Vec::with_capacity(n as usize);
+        let mut running = 0.0;
+        for i in 1..=n {
+            running += 1.0 / (i as f64).powf(theta);
+            prefix.push(running);
+        }
+        let zeta = running;
+        for c in &mut prefix {
+            *c /= zeta;
+        }
+        Self { prefix }
+    }
+
+    /// Draw a 0-indexed block rank: `u` below `c_k` selects the smallest such `k`.
+    fn sample(&self, rng: &mut ChaCha20Rng) -> u64 {
+        let u: f64 = rng.gen();
+        match self
+            .prefix
+            .binary_search_by(|c| c.partial_cmp(&u).unwrap())
+        {
+            Ok(idx) | Err(idx) => idx.min(self.prefix.len() - 1) as u64,
+        }
+    }
+}
+
+/// Scramble a Zipf rank through a cheap hash so hot blocks aren't clustered at
+/// the lowest addresses, then fold it back into the block range.
+fn scramble_rank(rank: u64, num_blocks: u64) -> u64 {
+    let mut x = rank.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^= x >> 31;
+    x % num_blocks
+}
+
+/// Build the Zipf table a generator should skew its addresses with, if the
+/// workload opted into `"distribution": "zip"` (or `"zipf"`). Shared by the
+/// Hotspot, Cache, and Database generators so they all honor the same knob.
+fn distribution_table(workload: &WorkloadSpec, num_blocks: u64) -> Option<ZipfTable> {
+    let distribution = workload
+        .params
+        .get("distribution")
+        .and_then(|v| v.as_str())
+        .unwrap_or("uniform");
+    if distribution != "zip" && distribution != "zipf" {
+        return None;
+    }
+    let theta = get_param_as_f64(&workload.params, "theta").unwrap_or(DEFAULT_ZIPF_THETA);
+    (theta > 0.0 && num_blocks > 0).then(|| ZipfTable::new(num_blocks, theta))
+}
+
 /// Generate a simple pattern from a workload specification
 pub fn generate_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     match workload.workload_type {
@@ -13,40 +125,297 @@ pub fn generate_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
         WorkloadType::Analytics => generate_analytics_pattern(workload),
         WorkloadType::Cache => generate_cache_pattern(workload),
         WorkloadType::Mixed => generate_mixed_pattern(workload),
+        WorkloadType::Zipfian => generate_zipfian_pattern(workload),
+        WorkloadType::Mix => generate_mix_pattern(workload),
+        WorkloadType::Tiered => generate_tiered_pattern(workload),
+    }
+}
+
+/// One sampled class in the operation mix. `Insert` and `Update` both become
+/// `Operation::Write`; `Remove` is modeled as a zero-size write so it's still
+/// visible as an op against the target key's address but moves no bytes.
+enum OpClass {
+    Read,
+    Insert,
+    Update,
+    Remove,
+}
+
+/// Cumulative probability mix over the four KV-style operation classes, read
+/// from the workload's `mix_read`/`mix_insert`/`mix_update`/`mix_remove`
+/// params. Must sum to 1.0 (within floating-point tolerance).
+struct OperationMix {
+    read: f64,
+    insert: f64,
+    update: f64,
+}
+
+impl OperationMix {
+    fn from_params(params: &HashMap<String, serde_json::Value>) -> Result<Self> {
+        let read = get_param_as_f64(params, "mix_read").unwrap_or(0.5);
+        let insert = get_param_as_f64(params, "mix_insert").unwrap_or(0.15);
+        let update = get_param_as_f64(params, "mix_update").unwrap_or(0.3);
+        let remove = get_param_as_f64(params, "mix_remove").unwrap_or(0.05);
+
+        let total = read + insert + update + remove;
+        if (total - 1.0).abs() > 1e-6 {
+            anyhow::bail!("mix_read + mix_insert + mix_update + mix_remove must sum to 1.0, got {total}");
+        }
+
+        Ok(Self { read, insert, update })
+    }
+
+    /// Sample a class from `u ~ [0, 1)` by walking the cumulative distribution
+    /// `read, read+insert, read+insert+update, 1.0`.
+    fn sample(&self, rng: &mut ChaCha20Rng) -> OpClass {
+        let u: f64 = rng.gen();
+        if u < self.read {
+            OpClass::Read
+        } else if u < self.read + self.insert {
+            OpClass::Insert
+        } else if u < self.read + self.insert + self.update {
+            OpClass::Update
+        } else {
+            OpClass::Remove
+        }
     }
 }
 
+fn generate_mix_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
+    let operations_count = get_param_as_u64(&workload.params, "operations").unwrap_or(1000);
+    let threads = get_param_as_u32(&workload.params, "threads").unwrap_or(4);
+    let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(4096);
+    let key_space = get_param_as_u64(&workload.params, "key_space").unwrap_or(1_000_000);
+    let prefill_fraction = get_param_as_f64(&workload.params, "prefill_fraction").unwrap_or(0.5);
+
+    let mix = OperationMix::from_params(&workload.params)?;
+
+    let keys_per_thread = key_space / threads as u64;
+    let prefill_per_thread = (prefill_fraction * keys_per_thread as f64) as u64;
+    let ops_per_thread = operations_count / threads as u64;
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
+        let thread_key_base = thread as u64 * keys_per_thread;
+        let mut ops = Vec::with_capacity((prefill_per_thread + ops_per_thread) as usize);
+
+        // Prefill phase: deterministically insert the thread's share of distinct keys.
+        let mut live_keys: Vec<u64> = (0..prefill_per_thread).collect();
+        for &key in &live_keys {
+            ops.push(Operation::Write {
+                addr: (thread_key_base + key) * block_size,
+                size: block_size,
+                thread,
+            });
+        }
+        let mut next_fresh_key = prefill_per_thread;
+
+        // Mixed phase: churn reads/inserts/updates/removes against the live key set.
+        for _ in 0..ops_per_thread {
+            match mix.sample(&mut rng) {
+                OpClass::Insert if next_fresh_key < keys_per_thread => {
+                    let key = next_fresh_key;
+                    next_fresh_key += 1;
+                    live_keys.push(key);
+                    ops.push(Operation::Write {
+                        addr: (thread_key_base + key) * block_size,
+                        size: block_size,
+                        thread,
+                    });
+                }
+                OpClass::Remove if !live_keys.is_empty() => {
+                    let idx = rng.gen_range(0..live_keys.len());
+                    let key = live_keys.swap_remove(idx);
+                    ops.push(Operation::Write {
+                        addr: (thread_key_base + key) * block_size,
+                        size: 0,
+                        thread,
+                    });
+                }
+                OpClass::Read if !live_keys.is_empty() => {
+                    let key = live_keys[rng.gen_range(0..live_keys.len())];
+                    ops.push(Operation::Read {
+                        addr: (thread_key_base + key) * block_size,
+                        size: block_size,
+                        thread,
+                    });
+                }
+                // Update, or any class whose precondition didn't hold (key space
+                // exhausted, or no live keys yet): fall back to a write against an
+                // existing key when one is available.
+                _ if !live_keys.is_empty() => {
+                    let key = live_keys[rng.gen_range(0..live_keys.len())];
+                    ops.push(Operation::Write {
+                        addr: (thread_key_base + key) * block_size,
+                        size: block_size,
+                        thread,
+                    });
+                }
+                _ => {}
+            }
+        }
+        ops
+    });
+
+    Ok(Pattern {
+        name: workload.name.clone(),
+        operations,
+    })
+}
+
+/// Tiered DRAM/CXL workload: addresses below `dram_size` model the hot set
+/// living in a DRAM `AddressMap` region, addresses from `dram_size` upward
+/// model the cold remainder living in a CXL region. When executed against an
+/// `AddressMap` whose `Dram`/`Cxl` regions are sized to match, these offsets
+/// land in the corresponding region's `base..base+size` window.
+fn generate_tiered_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
+    let operations_count = get_param_as_u64(&workload.params, "operations").unwrap_or(1000);
+    let threads = get_param_as_u32(&workload.params, "threads").unwrap_or(4);
+    let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(4096);
+    let read_ratio = get_param_as_f64(&workload.params, "read_ratio").unwrap_or(0.8);
+    let working_set_size = get_param_as_u64(&workload.params, "working_set_size").unwrap_or(1024 * 1024 * 1024);
+    let hot_fraction = get_param_as_f64(&workload.params, "hot_fraction").unwrap_or(0.1);
+    let hot_access_ratio = get_param_as_f64(&workload.params, "hot_access_ratio").unwrap_or(0.8);
+    let promotion_threshold = get_param_as_u64(&workload.params, "promotion_threshold").unwrap_or(5);
+    // Used as a divisor below; a `0` in the spec (syntactically valid JSON,
+    // same shape as every other numeric param here) falls back to the same
+    // default as an absent param instead of panicking.
+    let migration_block_size = match get_param_as_u64(&workload.params, "migration_block_size") {
+        Some(0) | None => 64 * 1024,
+        Some(size) => size,
+    };
+
+    let dram_size = (((working_set_size as f64) * hot_fraction) as u64).max(block_size);
+    let cxl_size = working_set_size.saturating_sub(dram_size).max(migration_block_size);
+    let dram_base = 0u64;
+    let cxl_base = dram_size;
+
+    let dram_blocks = (dram_size / block_size).max(1);
+    let cxl_blocks = (cxl_size / block_size).max(1);
+    let dram_migration_blocks = (dram_size / migration_block_size).max(1);
+    let cxl_migration_blocks = (cxl_size / migration_block_size).max(1);
+
+    let ops_per_thread = operations_count / threads as u64;
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+        // Per-block access counters for the cold remainder, so a block only
+        // migrates once it's been touched `promotion_threshold` times.
+        let mut cold_access_counts: HashMap<u64, u64> = HashMap::new();
+
+        for _ in 0..ops_per_thread {
+            if rng.gen::<f64>() < hot_access_ratio {
+                // Hot set: repeatedly read/write the small DRAM-resident range.
+                let addr = dram_base + rng.gen_range(0..dram_blocks) * block_size;
+                if rng.gen::<f64>() < read_ratio {
+                    ops.push(Operation::Read { addr, size: block_size, thread });
+                } else {
+                    ops.push(Operation::Write { addr, size: block_size, thread });
+                }
+                continue;
+            }
+
+            // Cold remainder: access a CXL-resident block and track its hit count.
+            let block = rng.gen_range(0..cxl_blocks);
+            let addr = cxl_base + block * block_size;
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr, size: block_size, thread });
+            } else {
+                ops.push(Operation::Write { addr, size: block_size, thread });
+            }
+
+            let count = cold_access_counts.entry(block).or_insert(0);
+            *count += 1;
+            if *count < promotion_threshold {
+                continue;
+            }
+            *count = 0;
+
+            // Promote: migrate the block's surrounding migration_block_size
+            // window from CXL to DRAM as one explicit Read+Write pair.
+            let migration_block = (block * block_size / migration_block_size) % cxl_migration_blocks;
+            let src_addr = cxl_base + migration_block * migration_block_size;
+            let dst_addr = dram_base + (migration_block % dram_migration_blocks) * migration_block_size;
+            ops.push(Operation::Read { addr: src_addr, size: migration_block_size, thread });
+            ops.push(Operation::Write { addr: dst_addr, size: migration_block_size, thread });
+        }
+        ops
+    });
+
+    Ok(Pattern {
+        name: workload.name.clone(),
+        operations,
+    })
+}
+
+fn generate_zipfian_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
+    let operations_count = get_param_as_u64(&workload.params, "operations").unwrap_or(1000);
+    let threads = get_param_as_u32(&workload.params, "threads").unwrap_or(4);
+    let read_ratio = get_param_as_f64(&workload.params, "read_ratio").unwrap_or(0.8);
+    let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(4096);
+    let memory_size = get_param_as_u64(&workload.params, "memory_size").unwrap_or(1024 * 1024 * 1024);
+    let theta = get_param_as_f64(&workload.params, "theta").unwrap_or(DEFAULT_ZIPF_THETA);
+
+    // `.max(1)` guards a `memory_size < block_size` config (valid input):
+    // without it, `num_blocks` is 0 and `ZipfTable::sample`/`scramble_rank`
+    // underflow/divide-by-zero below, the same hazard `distribution_table`
+    // already guards against for Hotspot/Cache/Database.
+    let num_blocks = (memory_size / block_size).max(1);
+    let table = (theta > 0.0 && num_blocks > 0).then(|| ZipfTable::new(num_blocks, theta));
+    let ops_per_thread = operations_count / threads as u64;
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
+        for _ in 0..ops_per_thread {
+            let rank = match &table {
+                Some(table) => table.sample(&mut rng),
+                None => rng.gen_range(0..num_blocks),
+            };
+            let addr = scramble_rank(rank, num_blocks) * block_size;
+
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr, size: block_size, thread });
+            } else {
+                ops.push(Operation::Write { addr, size: block_size, thread });
+            }
+        }
+        ops
+    });
+
+    Ok(Pattern {
+        name: workload.name.clone(),
+        operations,
+    })
+}
+
 fn generate_sequential_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let operations_count = get_param_as_u64(&workload.params, "operations").unwrap_or(1000);
     let threads = get_param_as_u32(&workload.params, "threads").unwrap_or(4);
     let read_ratio = get_param_as_f64(&workload.params, "read_ratio").unwrap_or(0.7);
     let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(4096);
     
-    let mut operations = Vec::new();
     let ops_per_thread = operations_count / threads as u64;
-    
-    for thread in 0..threads {
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
         let thread_base_addr = thread as u64 * 1024 * 1024; // 1MB per thread
         let mut current_addr = thread_base_addr;
-        
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for _ in 0..ops_per_thread {
-            if rand::random::<f64>() < read_ratio {
-                operations.push(Operation::Read {
-                    addr: current_addr,
-                    size: block_size,
-                    thread,
-                });
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr: current_addr, size: block_size, thread });
             } else {
-                operations.push(Operation::Write {
-                    addr: current_addr,
-                    size: block_size,
-                    thread,
-                });
+                ops.push(Operation::Write { addr: current_addr, size: block_size, thread });
             }
             current_addr += block_size;
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -59,31 +428,25 @@ fn generate_random_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let read_ratio = get_param_as_f64(&workload.params, "read_ratio").unwrap_or(0.7);
     let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(4096);
     let memory_size = get_param_as_u64(&workload.params, "memory_size").unwrap_or(1024 * 1024 * 1024); // 1GB
-    
-    let mut operations = Vec::new();
+
     let ops_per_thread = operations_count / threads as u64;
-    let mut rng = StdRng::seed_from_u64(42);
-    
-    for thread in 0..threads {
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for _ in 0..ops_per_thread {
             let random_addr = rng.gen_range(0..(memory_size - block_size) / block_size) * block_size;
-            
-            if rand::random::<f64>() < read_ratio {
-                operations.push(Operation::Read {
-                    addr: random_addr,
-                    size: block_size,
-                    thread,
-                });
+
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr: random_addr, size: block_size, thread });
             } else {
-                operations.push(Operation::Write {
-                    addr: random_addr,
-                    size: block_size,
-                    thread,
-                });
+                ops.push(Operation::Write { addr: random_addr, size: block_size, thread });
             }
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -98,37 +461,36 @@ fn generate_hotspot_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let hotspot_ratio = get_param_as_f64(&workload.params, "hotspot_ratio").unwrap_or(0.8);
     let memory_size = get_param_as_u64(&workload.params, "memory_size").unwrap_or(1024 * 1024 * 1024);
     
-    let mut operations = Vec::new();
     let ops_per_thread = operations_count / threads as u64;
     let hotspot_size = memory_size / 10; // Hot region is 10% of total memory
-    let mut rng = StdRng::seed_from_u64(42);
-    
-    for thread in 0..threads {
+    let hot_blocks = hotspot_size / block_size;
+    let hot_table = distribution_table(workload, hot_blocks);
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for _ in 0..ops_per_thread {
-            let addr = if rand::random::<f64>() < hotspot_ratio {
-                // Access hot region
-                rng.gen_range(0..(hotspot_size - block_size) / block_size) * block_size
+            let addr = if rng.gen::<f64>() < hotspot_ratio {
+                // Access hot region, skewed by the configured distribution
+                match &hot_table {
+                    Some(table) => scramble_rank(table.sample(&mut rng), hot_blocks) * block_size,
+                    None => rng.gen_range(0..(hotspot_size - block_size) / block_size) * block_size,
+                }
             } else {
                 // Access cold region
                 hotspot_size + rng.gen_range(0..((memory_size - hotspot_size - block_size) / block_size)) * block_size
             };
-            
-            if rand::random::<f64>() < read_ratio {
-                operations.push(Operation::Read {
-                    addr,
-                    size: block_size,
-                    thread,
-                });
+
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr, size: block_size, thread });
             } else {
-                operations.push(Operation::Write {
-                    addr,
-                    size: block_size,
-                    thread,
-                });
+                ops.push(Operation::Write { addr, size: block_size, thread });
             }
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -141,38 +503,39 @@ fn generate_database_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let read_ratio = get_param_as_f64(&workload.params, "read_ratio").unwrap_or(0.9);
     let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(8192);
     
-    let mut operations = Vec::new();
     let ops_per_thread = operations_count / threads as u64;
-    
-    for thread in 0..threads {
+    let seek_region_size = 5 * 1024 * 1024;
+    let seek_blocks = seek_region_size / block_size;
+    let seek_table = distribution_table(workload, seek_blocks);
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
         let thread_base_addr = thread as u64 * 10 * 1024 * 1024; // 10MB per thread
         let mut current_addr = thread_base_addr;
-        
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for i in 0..ops_per_thread {
             // Simulate database access: mostly sequential with some random seeks
             if i % 10 == 0 {
-                // Random seek every 10 operations
-                current_addr = thread_base_addr + (rand::random::<u64>() % (5 * 1024 * 1024)) & !(block_size - 1);
+                // Random seek every 10 operations, skewed by the configured distribution
+                current_addr = thread_base_addr
+                    + match &seek_table {
+                        Some(table) => scramble_rank(table.sample(&mut rng), seek_blocks) * block_size,
+                        None => (rng.gen::<u64>() % seek_region_size) & !(block_size - 1),
+                    };
             }
-            
-            if rand::random::<f64>() < read_ratio {
-                operations.push(Operation::Read {
-                    addr: current_addr,
-                    size: block_size,
-                    thread,
-                });
+
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr: current_addr, size: block_size, thread });
             } else {
-                operations.push(Operation::Write {
-                    addr: current_addr,
-                    size: block_size,
-                    thread,
-                });
+                ops.push(Operation::Write { addr: current_addr, size: block_size, thread });
             }
-            
+
             current_addr += block_size;
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -185,33 +548,27 @@ fn generate_analytics_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(1024 * 1024); // 1MB blocks
     let cpu_cycles = get_param_as_u64(&workload.params, "cpu_cycles").unwrap_or(1000000);
     
-    let mut operations = Vec::new();
     let ops_per_thread = operations_count / threads as u64;
-    
-    for thread in 0..threads {
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
         let thread_base_addr = thread as u64 * 100 * 1024 * 1024; // 100MB per thread
         let mut current_addr = thread_base_addr;
-        
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for i in 0..ops_per_thread {
             // Read large sequential blocks
-            operations.push(Operation::Read {
-                addr: current_addr,
-                size: block_size,
-                thread,
-            });
-            
+            ops.push(Operation::Read { addr: current_addr, size: block_size, thread });
+
             // Add CPU computation after every few reads
             if i % 5 == 4 {
-                operations.push(Operation::Cpu {
-                    cycles: cpu_cycles,
-                    thread,
-                });
+                ops.push(Operation::Cpu { cycles: cpu_cycles, thread });
             }
-            
+
             current_addr += block_size;
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -225,38 +582,38 @@ fn generate_cache_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let block_size = get_param_as_u64(&workload.params, "block_size").unwrap_or(64); // Cache line size
     let cache_miss_ratio = get_param_as_f64(&workload.params, "cache_miss_ratio").unwrap_or(0.1);
     
-    let mut operations = Vec::new();
     let ops_per_thread = operations_count / threads as u64;
     let cache_size = 32 * 1024; // 32KB cache per thread
-    
-    for thread in 0..threads {
+    let cache_blocks = cache_size / block_size;
+    let cache_table = distribution_table(workload, cache_blocks);
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
         let thread_base_addr = thread as u64 * 1024 * 1024; // 1MB per thread
-        
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for _ in 0..ops_per_thread {
-            let addr = if rand::random::<f64>() < cache_miss_ratio {
+            let addr = if rng.gen::<f64>() < cache_miss_ratio {
                 // Cache miss - access beyond cache
-                thread_base_addr + cache_size + (rand::random::<u64>() % (512 * 1024)) & !(block_size - 1)
+                (thread_base_addr + cache_size + (rng.gen::<u64>() % (512 * 1024))) & !(block_size - 1)
             } else {
-                // Cache hit - access within cache
-                thread_base_addr + (rand::random::<u64>() % cache_size) & !(block_size - 1)
+                // Cache hit - access within cache, skewed by the configured distribution
+                thread_base_addr
+                    + match &cache_table {
+                        Some(table) => scramble_rank(table.sample(&mut rng), cache_blocks) * block_size,
+                        None => (rng.gen::<u64>() % cache_size) & !(block_size - 1),
+                    }
             };
-            
-            if rand::random::<f64>() < read_ratio {
-                operations.push(Operation::Read {
-                    addr,
-                    size: block_size,
-                    thread,
-                });
+
+            if rng.gen::<f64>() < read_ratio {
+                ops.push(Operation::Read { addr, size: block_size, thread });
             } else {
-                operations.push(Operation::Write {
-                    addr,
-                    size: block_size,
-                    thread,
-                });
+                ops.push(Operation::Write { addr, size: block_size, thread });
             }
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -271,40 +628,31 @@ fn generate_mixed_pattern(workload: &WorkloadSpec) -> Result<Pattern> {
     let cpu_ratio = get_param_as_f64(&workload.params, "cpu_ratio").unwrap_or(0.2);
     let cpu_cycles = get_param_as_u64(&workload.params, "cpu_cycles").unwrap_or(10000);
     
-    let mut operations = Vec::new();
     let ops_per_thread = operations_count / threads as u64;
-    
-    for thread in 0..threads {
+
+    let operations = build_per_thread(workload, threads, operations_count, |thread| {
+        let mut rng = thread_rng(workload, thread);
         let thread_base_addr = thread as u64 * 1024 * 1024; // 1MB per thread
         let mut current_addr = thread_base_addr;
-        
+        let mut ops = Vec::with_capacity(ops_per_thread as usize);
+
         for _ in 0..ops_per_thread {
-            if rand::random::<f64>() < cpu_ratio {
+            if rng.gen::<f64>() < cpu_ratio {
                 // CPU operation
-                operations.push(Operation::Cpu {
-                    cycles: cpu_cycles,
-                    thread,
-                });
+                ops.push(Operation::Cpu { cycles: cpu_cycles, thread });
             } else {
                 // Memory operation
-                if rand::random::<f64>() < read_ratio {
-                    operations.push(Operation::Read {
-                        addr: current_addr,
-                        size: block_size,
-                        thread,
-                    });
+                if rng.gen::<f64>() < read_ratio {
+                    ops.push(Operation::Read { addr: current_addr, size: block_size, thread });
                 } else {
-                    operations.push(Operation::Write {
-                        addr: current_addr,
-                        size: block_size,
-                        thread,
-                    });
+                    ops.push(Operation::Write { addr: current_addr, size: block_size, thread });
                 }
                 current_addr += block_size;
             }
         }
-    }
-    
+        ops
+    });
+
     Ok(Pattern {
         name: workload.name.clone(),
         operations,
@@ -322,4 +670,51 @@ fn get_param_as_u32(params: &HashMap<String, serde_json::Value>, key: &str) -> O
 
 fn get_param_as_f64(params: &HashMap<String, serde_json::Value>, key: &str) -> Option<f64> {
     params.get(key)?.as_f64()
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workload(workload_type: WorkloadType, params: HashMap<String, serde_json::Value>) -> WorkloadSpec {
+        WorkloadSpec {
+            name: "test".to_string(),
+            workload_type,
+            params,
+            seed: None,
+            parallel: None,
+        }
+    }
+
+    #[test]
+    fn zipfian_pattern_handles_memory_size_smaller_than_block_size() {
+        // memory_size < block_size used to make num_blocks 0, underflowing
+        // ZipfTable::sample and dividing by zero in scramble_rank.
+        let mut params = HashMap::new();
+        params.insert("operations".to_string(), serde_json::json!(16));
+        params.insert("threads".to_string(), serde_json::json!(1));
+        params.insert("memory_size".to_string(), serde_json::json!(100));
+        let spec = workload(WorkloadType::Zipfian, params);
+
+        let pattern = generate_pattern(&spec).unwrap();
+        assert_eq!(pattern.operations.len(), 16);
+    }
+
+    #[test]
+    fn tiered_pattern_handles_zero_migration_block_size() {
+        // migration_block_size: 0 used to divide-by-zero computing
+        // dram_migration_blocks/cxl_migration_blocks.
+        let mut params = HashMap::new();
+        params.insert("operations".to_string(), serde_json::json!(32));
+        params.insert("threads".to_string(), serde_json::json!(1));
+        params.insert("promotion_threshold".to_string(), serde_json::json!(1));
+        params.insert("migration_block_size".to_string(), serde_json::json!(0));
+        let spec = workload(WorkloadType::Tiered, params);
+
+        let pattern = generate_pattern(&spec).unwrap();
+        // Every cold access promotes with threshold 1, so op count grows
+        // beyond the 32 base hot/cold accesses by one migration Read+Write
+        // pair per cold access; just check generation didn't panic.
+        assert!(pattern.operations.len() >= 32);
+    }
+}
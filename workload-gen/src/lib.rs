@@ -1,8 +1,11 @@
 //! CXL Workload Generator - A simple pattern executor for CXL testing
 
+pub mod cache;
 pub mod common;
+pub mod distributed;
 pub mod executor;
 pub mod generator;
+pub mod pack;
 
 pub use common::*;
 pub use executor::*;
@@ -2,11 +2,15 @@ use clap::{Parser, Subcommand};
 use anyhow::Result;
 use std::path::PathBuf;
 
+mod cache;
 mod common;
+mod distributed;
 mod executor;
 mod generator;
+mod pack;
 
 use common::{Pattern, WorkloadSpec, ExecutionResults, AddressMap, ScheduleMap, ExecutionConfig};
+use distributed::ClusterConfig;
 use executor::PatternExecutor;
 use generator::generate_pattern;
 
@@ -49,17 +53,23 @@ enum Commands {
         /// Output results to file
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Write periodic metrics snapshots (JSON-lines) to this file
+        /// instead of stdout, when `execution_config.metrics_interval` is set
+        #[arg(long)]
+        metrics_output: Option<PathBuf>,
     },
-    
+
     /// Generate a pattern from workload specification
     Generate {
         /// Workload type
         #[arg(short, long)]
         workload_type: Option<String>,
         
-        /// Path to workload specification JSON file
+        /// Path to a workload specification JSON file, or an inline
+        /// key=value spec such as "type=random,ops=1000,threads=8,read_ratio=0.7,bs=4K"
         #[arg(short, long)]
-        workload: Option<PathBuf>,
+        workload: Option<String>,
         
         /// Number of operations to generate
         #[arg(long, default_value = "1000")]
@@ -91,19 +101,63 @@ enum Commands {
         /// Path to pattern JSON file
         #[arg(short, long)]
         pattern: PathBuf,
-        
+
         /// Analyze scheduling requirements
         #[arg(long)]
         analyze: bool,
-        
+
         /// Generate recommended schedule config
         #[arg(long)]
         generate_config: bool,
-        
+
+        /// Address mapping configuration, used to report the DRAM-vs-CXL
+        /// byte split of a `--analyze` run
+        #[arg(short, long)]
+        address_map: Option<PathBuf>,
+
         /// Output config file
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+
+    /// Run a pattern across multiple worker nodes, or act as a worker
+    Cluster {
+        /// Listen on this address as a worker instead of coordinating
+        #[arg(long)]
+        serve: Option<String>,
+
+        /// Path to pattern JSON file (coordinator mode)
+        #[arg(short, long)]
+        pattern: Option<PathBuf>,
+
+        /// Address mapping configuration
+        #[arg(short, long)]
+        address_map: Option<PathBuf>,
+
+        /// Schedule mapping configuration
+        #[arg(short, long)]
+        schedule_map: Option<PathBuf>,
+
+        /// Execution configuration
+        #[arg(short, long)]
+        execution_config: Option<PathBuf>,
+
+        /// Path to cluster node list JSON file
+        #[arg(long)]
+        nodes: Option<PathBuf>,
+
+        /// Override duration in seconds
+        #[arg(short, long)]
+        duration: Option<u64>,
+
+        /// Verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Output results to file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 fn main() -> Result<()> {
@@ -111,16 +165,17 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Exec { 
-            pattern, 
+        Commands::Exec {
+            pattern,
             address_map,
             schedule_map,
             execution_config,
             duration,
-            verbose, 
-            output 
+            verbose,
+            output,
+            metrics_output,
         } => {
-            execute_command(pattern, address_map, schedule_map, execution_config, duration, verbose, output)
+            execute_command(pattern, address_map, schedule_map, execution_config, duration, verbose, output, metrics_output)
         }
         Commands::Generate { 
             workload_type,
@@ -138,9 +193,29 @@ fn main() -> Result<()> {
             pattern,
             analyze,
             generate_config,
+            address_map,
+            output,
+        } => {
+            schedule_command(pattern, analyze, generate_config, address_map, output)
+        }
+        Commands::Cluster {
+            serve,
+            pattern,
+            address_map,
+            schedule_map,
+            execution_config,
+            nodes,
+            duration,
+            verbose,
             output,
         } => {
-            schedule_command(pattern, analyze, generate_config, output)
+            if let Some(addr) = serve {
+                distributed::serve(&addr)
+            } else {
+                let pattern = pattern.ok_or_else(|| anyhow::anyhow!("--pattern is required in coordinator mode"))?;
+                let nodes = nodes.ok_or_else(|| anyhow::anyhow!("--nodes is required in coordinator mode"))?;
+                cluster_command(pattern, address_map, schedule_map, execution_config, nodes, duration, verbose, output)
+            }
         }
     }
 }
@@ -153,11 +228,11 @@ fn execute_command(
     duration_override: Option<u64>,
     verbose: bool,
     output_path: Option<PathBuf>,
+    metrics_output_path: Option<PathBuf>,
 ) -> Result<()> {
     // Load pattern
-    let pattern_content = std::fs::read_to_string(&pattern_path)?;
-    let pattern: Pattern = serde_json::from_str(&pattern_content)?;
-    
+    let pattern: Pattern = pack::load_pattern(&pattern_path)?;
+
     // Load optional configurations
     let address_map = if let Some(path) = address_map_path {
         let content = std::fs::read_to_string(&path)?;
@@ -165,7 +240,7 @@ fn execute_command(
     } else {
         None
     };
-    
+
     let schedule_map = if let Some(path) = schedule_map_path {
         let content = std::fs::read_to_string(&path)?;
         Some(serde_json::from_str::<ScheduleMap>(&content)?)
@@ -222,7 +297,11 @@ fn execute_command(
     }
     
     // Execute pattern
-    let executor = PatternExecutor::new(pattern, address_map, schedule_map, execution_config)?;
+    let mut spec = common::PatternSpec::from_pattern(pattern, address_map.as_ref(), schedule_map.as_ref(), &execution_config)?;
+    if let Some(path) = metrics_output_path {
+        spec.metrics_output_path = Some(path.to_string_lossy().into_owned());
+    }
+    let executor = PatternExecutor::new(spec)?;
     let results = executor.execute()?;
     
     // Display results
@@ -238,9 +317,82 @@ fn execute_command(
     Ok(())
 }
 
+fn cluster_command(
+    pattern_path: PathBuf,
+    address_map_path: Option<PathBuf>,
+    schedule_map_path: Option<PathBuf>,
+    execution_config_path: Option<PathBuf>,
+    nodes_path: PathBuf,
+    duration_override: Option<u64>,
+    verbose: bool,
+    output_path: Option<PathBuf>,
+) -> Result<()> {
+    // Load pattern
+    let pattern: Pattern = pack::load_pattern(&pattern_path)?;
+
+    // Load optional configurations
+    let address_map = if let Some(path) = address_map_path {
+        let content = std::fs::read_to_string(&path)?;
+        Some(serde_json::from_str::<AddressMap>(&content)?)
+    } else {
+        None
+    };
+
+    let schedule_map = if let Some(path) = schedule_map_path {
+        let content = std::fs::read_to_string(&path)?;
+        Some(serde_json::from_str::<ScheduleMap>(&content)?)
+    } else {
+        None
+    };
+
+    let mut execution_config = if let Some(path) = execution_config_path {
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str::<ExecutionConfig>(&content)?
+    } else {
+        ExecutionConfig {
+            duration_seconds: Some(10),
+            rate_limit: None,
+            warmup_seconds: None,
+            metrics_interval: None,
+        }
+    };
+
+    // Apply duration override
+    if let Some(duration) = duration_override {
+        execution_config.duration_seconds = Some(duration);
+    }
+
+    let nodes_content = std::fs::read_to_string(&nodes_path)?;
+    let cluster: ClusterConfig = serde_json::from_str(&nodes_content)?;
+
+    if verbose {
+        println!("=== Cluster Execution ===");
+        println!("Pattern: {}", pattern.name);
+        println!("Operations: {}", pattern.operations.len());
+        println!("Nodes: {}", cluster.nodes.len());
+        println!("Duration: {:?} seconds", execution_config.duration_seconds);
+        println!();
+    }
+
+    // Dispatch to every node and fold the results together
+    let results = distributed::run_cluster(pattern, address_map, schedule_map, execution_config, cluster)?;
+
+    // Display results
+    display_results(&results, verbose);
+
+    // Save results if requested
+    if let Some(output) = output_path {
+        let results_json = serde_json::to_string_pretty(&results)?;
+        std::fs::write(output, results_json)?;
+        println!("Results saved to file");
+    }
+
+    Ok(())
+}
+
 fn generate_command(
     workload_type: Option<String>,
-    workload_path: Option<PathBuf>,
+    workload_spec: Option<String>,
     operations: u64,
     threads: u32,
     read_ratio: f64,
@@ -248,44 +400,41 @@ fn generate_command(
     output_path: PathBuf,
     verbose: bool,
 ) -> Result<()> {
-    let pattern = if let Some(workload_path) = workload_path {
-        // Generate from workload file
-        let workload_content = std::fs::read_to_string(&workload_path)?;
-        let workload: WorkloadSpec = serde_json::from_str(&workload_content)?;
-        
+    let pattern = if let Some(workload_spec) = workload_spec {
+        // Either an inline "key=value,..." spec or a path to a workload JSON file.
+        let workload: WorkloadSpec = if workload_spec.contains('=') {
+            workload_spec.parse()?
+        } else {
+            let workload_content = std::fs::read_to_string(&workload_spec)?;
+            serde_json::from_str(&workload_content)?
+        };
+
         if verbose {
             println!("=== Pattern Generation ===");
             println!("Workload: {}", workload.name);
             println!("Type: {:?}", workload.workload_type);
             println!();
         }
-        
+
         generate_pattern(&workload)?
     } else if let Some(wl_type) = workload_type {
         // Generate from command line parameters
-        let workload_type = match wl_type.as_str() {
-            "sequential" => common::WorkloadType::Sequential,
-            "random" => common::WorkloadType::Random,
-            "hotspot" => common::WorkloadType::Hotspot,
-            "database" => common::WorkloadType::Database,
-            "analytics" => common::WorkloadType::Analytics,
-            "cache" => common::WorkloadType::Cache,
-            "mixed" => common::WorkloadType::Mixed,
-            _ => return Err(anyhow::anyhow!("Unknown workload type: {}", wl_type)),
-        };
-        
+        let workload_type = common::parse_workload_type(&wl_type)?;
+
         let mut params = std::collections::HashMap::new();
         params.insert("operations".to_string(), serde_json::Value::Number(operations.into()));
         params.insert("threads".to_string(), serde_json::Value::Number(threads.into()));
         params.insert("read_ratio".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(read_ratio).unwrap()));
         params.insert("block_size".to_string(), serde_json::Value::Number(block_size.into()));
-        
+
         let workload = WorkloadSpec {
             name: format!("{}_generated", wl_type),
             workload_type,
             params,
+            seed: None,
+            parallel: None,
         };
-        
+
         if verbose {
             println!("=== Pattern Generation ===");
             println!("Type: {:?}", workload.workload_type);
@@ -295,7 +444,7 @@ fn generate_command(
             println!("Block size: {}", block_size);
             println!();
         }
-        
+
         generate_pattern(&workload)?
     } else {
         return Err(anyhow::anyhow!("Must specify either --workload-type or --workload"));
@@ -307,10 +456,11 @@ fn generate_command(
         println!();
     }
     
-    // Save pattern
-    let pattern_json = serde_json::to_string_pretty(&pattern)?;
-    std::fs::write(&output_path, pattern_json)?;
-    
+    // Save pattern. A `.pack` extension saves the compact binary trace format
+    // instead of JSON, so a large generated pattern can be replayed by `exec`
+    // without regenerating or re-parsing it every time.
+    pack::save_pattern(&pattern, &output_path)?;
+
     println!("Pattern generated and saved to: {}", output_path.display());
     
     Ok(())
@@ -320,12 +470,19 @@ fn schedule_command(
     pattern_path: PathBuf,
     analyze: bool,
     generate_config: bool,
+    address_map_path: Option<PathBuf>,
     output_path: Option<PathBuf>,
 ) -> Result<()> {
     // Load pattern
-    let pattern_content = std::fs::read_to_string(&pattern_path)?;
-    let pattern: Pattern = serde_json::from_str(&pattern_content)?;
-    
+    let pattern: Pattern = pack::load_pattern(&pattern_path)?;
+
+    let address_map = if let Some(path) = address_map_path {
+        let content = std::fs::read_to_string(&path)?;
+        Some(serde_json::from_str::<AddressMap>(&content)?)
+    } else {
+        None
+    };
+
     if analyze {
         println!("=== Schedule Analysis ===");
         println!("Pattern: {}", pattern.name);
@@ -370,6 +527,37 @@ fn schedule_command(
         if min_addr != u64::MAX {
             println!("Address range: 0x{:x} - 0x{:x} ({} bytes)", min_addr, max_addr, max_addr - min_addr);
         }
+
+        // Sanity-check the DRAM/CXL tiering ratio by resolving every op's
+        // address against the supplied AddressMap's region bases.
+        if let Some(ref addr_map) = address_map {
+            let mut dram_bytes = 0u64;
+            let mut cxl_bytes = 0u64;
+            let mut unmapped_bytes = 0u64;
+
+            for op in &pattern.operations {
+                if let common::Operation::Read { addr, size, .. } | common::Operation::Write { addr, size, .. } = op {
+                    let region = addr_map
+                        .memory_regions
+                        .iter()
+                        .find(|r| *addr >= r.base && *addr < r.base + r.size);
+                    match region.map(|r| &r.region_type) {
+                        Some(common::RegionType::Dram) => dram_bytes += size,
+                        Some(common::RegionType::Cxl) => cxl_bytes += size,
+                        _ => unmapped_bytes += size,
+                    }
+                }
+            }
+
+            let total_bytes = dram_bytes + cxl_bytes + unmapped_bytes;
+            if total_bytes > 0 {
+                println!("DRAM bytes: {} ({:.1}%)", dram_bytes, dram_bytes as f64 / total_bytes as f64 * 100.0);
+                println!("CXL bytes: {} ({:.1}%)", cxl_bytes, cxl_bytes as f64 / total_bytes as f64 * 100.0);
+                if unmapped_bytes > 0 {
+                    println!("Unmapped bytes: {} ({:.1}%)", unmapped_bytes, unmapped_bytes as f64 / total_bytes as f64 * 100.0);
+                }
+            }
+        }
     }
     
     if generate_config {
@@ -441,22 +629,44 @@ fn display_results(results: &ExecutionResults, verbose: bool) {
     }
     
     if results.total_bytes_read > 0 {
-        println!("Read: {} bytes, {:.2} MB/s", 
-            results.total_bytes_read, 
+        println!("Read: {} bytes, {:.2} MB/s",
+            results.total_bytes_read,
             results.read_throughput_mbps
         );
+        println!("Read latency p50/p90/p99/p999 (ns): {}/{}/{}/{}",
+            results.read_latency_percentiles.p50_ns,
+            results.read_latency_percentiles.p90_ns,
+            results.read_latency_percentiles.p99_ns,
+            results.read_latency_percentiles.p999_ns,
+        );
     }
-    
+
     if results.total_bytes_written > 0 {
-        println!("Write: {} bytes, {:.2} MB/s", 
-            results.total_bytes_written, 
+        println!("Write: {} bytes, {:.2} MB/s",
+            results.total_bytes_written,
             results.write_throughput_mbps
         );
+        println!("Write latency p50/p90/p99/p999 (ns): {}/{}/{}/{}",
+            results.write_latency_percentiles.p50_ns,
+            results.write_latency_percentiles.p90_ns,
+            results.write_latency_percentiles.p99_ns,
+            results.write_latency_percentiles.p999_ns,
+        );
     }
     
     if results.total_cpu_cycles > 0 {
         println!("CPU cycles: {}", results.total_cpu_cycles);
     }
+
+    if let Some(cache_stats) = &results.cache_stats {
+        let total = cache_stats.hits + cache_stats.misses;
+        let hit_rate = if total > 0 { cache_stats.hits as f64 / total as f64 * 100.0 } else { 0.0 };
+        println!("Cache: {} hits, {} misses ({:.1}% hit rate)",
+            cache_stats.hits,
+            cache_stats.misses,
+            hit_rate
+        );
+    }
     
     if verbose {
         println!("\n=== Per-Thread Stats ===");
@@ -470,6 +680,25 @@ fn display_results(results: &ExecutionResults, verbose: bool) {
                 thread_stat.min_latency_ns,
                 thread_stat.max_latency_ns
             );
+            if thread_stat.atomic_retries > 0 {
+                println!("  atomic retries: {}", thread_stat.atomic_retries);
+            }
+            if thread_stat.bytes_read > 0 {
+                println!("  read p50/p90/p99/p999 (ns): {}/{}/{}/{}",
+                    thread_stat.read_latency_percentiles.p50_ns,
+                    thread_stat.read_latency_percentiles.p90_ns,
+                    thread_stat.read_latency_percentiles.p99_ns,
+                    thread_stat.read_latency_percentiles.p999_ns,
+                );
+            }
+            if thread_stat.bytes_written > 0 {
+                println!("  write p50/p90/p99/p999 (ns): {}/{}/{}/{}",
+                    thread_stat.write_latency_percentiles.p50_ns,
+                    thread_stat.write_latency_percentiles.p90_ns,
+                    thread_stat.write_latency_percentiles.p99_ns,
+                    thread_stat.write_latency_percentiles.p999_ns,
+                );
+            }
         }
     }
 }
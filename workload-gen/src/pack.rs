@@ -0,0 +1,261 @@
+//! Compact, compressed, checksummed binary trace format for `Pattern`s.
+//!
+//! Regenerating a pattern with tens of millions of operations is expensive,
+//! and the JSON form is bulky. `Pattern::write_pack`/`Pattern::read_pack`
+//! save/restore one to a self-describing binary container instead: a fixed
+//! header, a variable-length operation stream, split into fixed-size blocks
+//! that are individually zlib-compressed and checksummed so partial
+//! corruption is caught before it silently produces a bad replay.
+
+use crate::common::{Operation, Pattern};
+use anyhow::{bail, Result};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: [u8; 4] = *b"CXLP";
+const FORMAT_VERSION: u32 = 1;
+/// Operations are buffered into blocks of this size (pre-compression) before
+/// each block is compressed and checksummed independently.
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+const OP_TAG_READ: u8 = 0;
+const OP_TAG_WRITE: u8 = 1;
+const OP_TAG_CPU: u8 = 2;
+const OP_TAG_GPU: u8 = 3;
+
+/// Load a pattern from `path`: the compact binary trace format for a `.pack`
+/// extension (written by [`save_pattern`] or [`Pattern::write_pack`]
+/// directly), pattern JSON otherwise. Shared by every CLI command that takes
+/// a `--pattern` path.
+pub fn load_pattern<P: AsRef<Path>>(path: P) -> Result<Pattern> {
+    let path = path.as_ref();
+    if path.extension().and_then(|ext| ext.to_str()) == Some("pack") {
+        Pattern::read_pack(path)
+    } else {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// Save `pattern` to `path`: the compact binary trace format for a `.pack`
+/// extension, pretty-printed pattern JSON otherwise.
+pub fn save_pattern<P: AsRef<Path>>(pattern: &Pattern, path: P) -> Result<()> {
+    let path = path.as_ref();
+    if path.extension().and_then(|ext| ext.to_str()) == Some("pack") {
+        pattern.write_pack(path)
+    } else {
+        let json = serde_json::to_string_pretty(pattern)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+impl Pattern {
+    /// Write this pattern to `path` as a compressed, checksummed binary trace.
+    pub fn write_pack<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        out.write_all(&MAGIC)?;
+        out.write_u32::<LittleEndian>(FORMAT_VERSION)?;
+        write_string(&mut out, &self.name)?;
+        out.write_u64::<LittleEndian>(self.operations.len() as u64)?;
+
+        let mut block = Vec::with_capacity(BLOCK_SIZE);
+        for op in &self.operations {
+            encode_operation(&mut block, op)?;
+            if block.len() >= BLOCK_SIZE {
+                write_block(&mut out, &block)?;
+                block.clear();
+            }
+        }
+        if !block.is_empty() {
+            write_block(&mut out, &block)?;
+        }
+        // Zero-length block marks the end of the operation stream.
+        write_block(&mut out, &[])?;
+
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Read a pattern previously written by [`Pattern::write_pack`], verifying
+    /// the magic, version, and each block's checksum before decoding.
+    pub fn read_pack<P: AsRef<Path>>(path: P) -> Result<Pattern> {
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            bail!("not a CXL pattern pack (bad magic)");
+        }
+
+        let version = input.read_u32::<LittleEndian>()?;
+        if version != FORMAT_VERSION {
+            bail!("unsupported pattern pack version {version}, expected {FORMAT_VERSION}");
+        }
+
+        let name = read_string(&mut input)?;
+        let op_count = input.read_u64::<LittleEndian>()?;
+
+        let mut operations = Vec::with_capacity(op_count as usize);
+        let mut pending = Vec::new();
+        loop {
+            let block = read_block(&mut input)?;
+            if block.is_empty() {
+                break;
+            }
+            pending.extend_from_slice(&block);
+
+            let mut cursor = &pending[..];
+            let mut consumed = 0;
+            while let Some((op, used)) = try_decode_operation(cursor)? {
+                operations.push(op);
+                cursor = &cursor[used..];
+                consumed += used;
+            }
+            pending.drain(..consumed);
+        }
+
+        if operations.len() as u64 != op_count {
+            bail!(
+                "pattern pack truncated: header promised {op_count} operations, decoded {}",
+                operations.len()
+            );
+        }
+
+        Ok(Pattern { name, operations })
+    }
+}
+
+fn write_string<W: Write>(out: &mut W, s: &str) -> Result<()> {
+    out.write_u32::<LittleEndian>(s.len() as u32)?;
+    out.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(input: &mut R) -> Result<String> {
+    let len = input.read_u32::<LittleEndian>()? as usize;
+    let mut buf = vec![0u8; len];
+    input.read_exact(&mut buf)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+fn write_block<W: Write>(out: &mut W, raw: &[u8]) -> Result<()> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(raw)?;
+    let compressed = encoder.finish()?;
+    let checksum = crc32fast::hash(&compressed);
+
+    out.write_u32::<LittleEndian>(raw.len() as u32)?;
+    out.write_u32::<LittleEndian>(compressed.len() as u32)?;
+    out.write_u32::<LittleEndian>(checksum)?;
+    out.write_all(&compressed)?;
+    Ok(())
+}
+
+fn read_block<R: Read>(input: &mut R) -> Result<Vec<u8>> {
+    let raw_len = input.read_u32::<LittleEndian>()? as usize;
+    let compressed_len = input.read_u32::<LittleEndian>()? as usize;
+    let expected_checksum = input.read_u32::<LittleEndian>()?;
+
+    let mut compressed = vec![0u8; compressed_len];
+    input.read_exact(&mut compressed)?;
+
+    let checksum = crc32fast::hash(&compressed);
+    if checksum != expected_checksum {
+        bail!("pattern pack block failed CRC32 check (corrupt file)");
+    }
+
+    let mut raw = Vec::with_capacity(raw_len);
+    ZlibDecoder::new(&compressed[..]).read_to_end(&mut raw)?;
+    if raw.len() != raw_len {
+        bail!("pattern pack block decompressed to {} bytes, expected {raw_len}", raw.len());
+    }
+    Ok(raw)
+}
+
+fn encode_operation(out: &mut Vec<u8>, op: &Operation) -> Result<()> {
+    match op {
+        Operation::Read { addr, size, thread } => {
+            out.push(OP_TAG_READ);
+            out.write_u64::<LittleEndian>(*addr)?;
+            out.write_u64::<LittleEndian>(*size)?;
+            out.write_u32::<LittleEndian>(*thread)?;
+        }
+        Operation::Write { addr, size, thread } => {
+            out.push(OP_TAG_WRITE);
+            out.write_u64::<LittleEndian>(*addr)?;
+            out.write_u64::<LittleEndian>(*size)?;
+            out.write_u32::<LittleEndian>(*thread)?;
+        }
+        Operation::Cpu { cycles, thread } => {
+            out.push(OP_TAG_CPU);
+            out.write_u64::<LittleEndian>(*cycles)?;
+            out.write_u32::<LittleEndian>(*thread)?;
+        }
+        Operation::Gpu { kernel, thread } => {
+            out.push(OP_TAG_GPU);
+            out.write_u32::<LittleEndian>(kernel.len() as u32)?;
+            out.extend_from_slice(kernel.as_bytes());
+            out.write_u32::<LittleEndian>(*thread)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decode one operation from the front of `buf`, if a full record is present.
+/// Returns `None` (rather than erroring) on a partial trailing record so the
+/// caller can pull in the next block and retry.
+fn try_decode_operation(mut buf: &[u8]) -> Result<Option<(Operation, usize)>> {
+    let start_len = buf.len();
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    let tag = buf[0];
+    buf = &buf[1..];
+
+    macro_rules! need {
+        ($n:expr) => {
+            if buf.len() < $n {
+                return Ok(None);
+            }
+        };
+    }
+
+    let op = match tag {
+        OP_TAG_READ | OP_TAG_WRITE => {
+            need!(20);
+            let addr = buf.read_u64::<LittleEndian>()?;
+            let size = buf.read_u64::<LittleEndian>()?;
+            let thread = buf.read_u32::<LittleEndian>()?;
+            if tag == OP_TAG_READ {
+                Operation::Read { addr, size, thread }
+            } else {
+                Operation::Write { addr, size, thread }
+            }
+        }
+        OP_TAG_CPU => {
+            need!(12);
+            let cycles = buf.read_u64::<LittleEndian>()?;
+            let thread = buf.read_u32::<LittleEndian>()?;
+            Operation::Cpu { cycles, thread }
+        }
+        OP_TAG_GPU => {
+            need!(4);
+            let kernel_len = buf.read_u32::<LittleEndian>()? as usize;
+            need!(kernel_len + 4);
+            let kernel = String::from_utf8(buf[..kernel_len].to_vec())?;
+            buf = &buf[kernel_len..];
+            let thread = buf.read_u32::<LittleEndian>()?;
+            Operation::Gpu { kernel, thread }
+        }
+        other => bail!("unknown operation tag {other} in pattern pack"),
+    };
+
+    Ok(Some((op, start_len - buf.len())))
+}